@@ -1,37 +1,110 @@
 //! Certificate chain generation and validation
 use std::{
-    net::Ipv4Addr,
+    cell::RefCell,
+    ffi::c_int,
+    fmt, fs,
+    net::IpAddr,
     ops::Add,
+    path::Path,
     time::{Duration, SystemTime},
 };
 
 use openssl::{
-    asn1::{Asn1Integer, Asn1Time},
-    bn::BigNum,
-    hash::MessageDigest,
+    asn1::{Asn1Object, Asn1ObjectRef, Asn1Time},
+    error::ErrorStack,
+    ssl::SslFiletype,
     stack::Stack,
-    x509::{self, store::X509StoreBuilder, X509StoreContext, X509},
+    x509::{
+        self,
+        store::{X509Lookup, X509StoreBuilder},
+        verify::{X509CheckFlags, X509VerifyFlags, X509VerifyParam},
+        X509PurposeId, X509Ref, X509StoreContext, X509StoreContextRef, X509VerifyResult, X509,
+    },
 };
+use openssl_sys::{
+    X509_STORE_CTX, X509_V_ERR_CERT_HAS_EXPIRED, X509_V_ERR_CERT_NOT_YET_VALID,
+    X509_V_ERR_EMAIL_MISMATCH, X509_V_ERR_HOSTNAME_MISMATCH, X509_V_ERR_INVALID_PURPOSE,
+    X509_V_ERR_IP_ADDRESS_MISMATCH,
+};
+
+use foreign_types::ForeignTypeRef;
 
-use crate::model::{CertName, CertUsage, Certificate, KeyStore, PkiError, PrivateKey, Result};
+use crate::{
+    extension::{
+        self, CertificatePolicy, Extension, NameConstraint, RevocationInfo,
+        OID_ANY_EXTENDED_KEY_USAGE, OID_AUTHORITY_INFO_ACCESS, OID_AUTHORITY_KEY_IDENTIFIER,
+        OID_BASIC_CONSTRAINTS, OID_CERTIFICATE_POLICIES, OID_CRL_DISTRIBUTION_POINTS,
+        OID_EXTENDED_KEY_USAGE, OID_FRESHEST_CRL, OID_INHIBIT_ANY_POLICY, OID_KEY_USAGE,
+        OID_NAME_CONSTRAINTS, OID_POLICY_CONSTRAINTS, OID_POLICY_MAPPINGS, OID_SUBJECT_ALT_NAME,
+        OID_SUBJECT_KEY_IDENTIFIER,
+    },
+    ffi,
+    model::{
+        CertName, CertNameRef, CertUsage, Certificate, KeyStore, PkiError, PrivateKey, PublicKey,
+        Result,
+    },
+    name::oid_string,
+    request::CertificateRequest,
+    serial::{RandomSerialAllocator, SerialAllocator, SerialNumber},
+    signer::{self, Signer},
+    usage::UsageProfile,
+    validity::CertTime,
+};
 
 /// Default validity days of the entity certificate
 pub const DEFAULT_CERT_VALIDITY_DAYS: u64 = 825;
 
+/// keyCertSign bit as returned by `X509_get_key_usage`
+const KU_KEY_CERT_SIGN: u32 = 0x0004;
+
+/// digitalSignature bit as returned by `X509_get_key_usage`
+const KU_DIGITAL_SIGNATURE: u32 = 0x0080;
+
+/// Maximum number of candidate paths tried when building a chain from unordered intermediates
+const MAX_CANDIDATE_PATHS: usize = 32;
+
 /// Default RSA key size
 pub const DEFAULT_RSA_KEY_LENGTH: u32 = 2048;
 
+/// Certificates included into the key store created by [`CertificateBuilder`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChainComposition {
+    /// The new certificate only
+    LeafOnly,
+    /// The new certificate and the issuer chain up to the last intermediate, without the root
+    WithoutRoot,
+    /// The new certificate and the whole issuer chain including the root
+    #[default]
+    Full,
+}
+
 /// Certificate builder is used to create X.509 certificate chains
 pub struct CertificateBuilder<'a> {
-    signer: Option<&'a KeyStore>,
+    signer: Option<&'a dyn Signer>,
     subject: Option<CertName>,
-    usage: CertUsage,
+    usage: UsageProfile,
     alt_names: Vec<String>,
-    not_before: SystemTime,
-    not_after: SystemTime,
-    serial_number: Option<u128>,
-    path_len: i32,
+    not_before: CertTime,
+    not_after: CertTime,
+    serial_number: Option<SerialNumber>,
+    serial_allocator: Option<&'a dyn SerialAllocator>,
+    path_len: Option<u32>,
+    basic_constraints_critical: Option<bool>,
+    end_entity_basic_constraints: bool,
     private_key: Option<PrivateKey>,
+    public_key: Option<PublicKey>,
+    issuer_chain: Option<Vec<Certificate>>,
+    chain_composition: ChainComposition,
+    permitted_names: Vec<NameConstraint>,
+    excluded_names: Vec<NameConstraint>,
+    policies: Vec<CertificatePolicy>,
+    policy_mappings: Vec<(String, String)>,
+    policy_constraints: Option<(Option<u32>, Option<u32>)>,
+    inhibit_any_policy: Option<u32>,
+    revocation_info: Option<RevocationInfo>,
+    extensions: Vec<Extension>,
+    removed_extensions: Vec<String>,
+    config: Option<(String, Option<String>)>,
 }
 
 impl<'a> Default for CertificateBuilder<'a> {
@@ -46,16 +119,153 @@ impl<'a> CertificateBuilder<'a> {
         Self {
             signer: None,
             subject: None,
-            usage: CertUsage::TlsServer,
+            usage: CertUsage::TlsServer.into(),
             alt_names: Vec::new(),
-            not_before: SystemTime::now(),
-            not_after: SystemTime::now().add(Duration::from_secs(
-                DEFAULT_CERT_VALIDITY_DAYS * 24 * 60 * 60,
-            )),
+            not_before: CertTime::now(),
+            not_after: SystemTime::now()
+                .add(Duration::from_secs(
+                    DEFAULT_CERT_VALIDITY_DAYS * 24 * 60 * 60,
+                ))
+                .into(),
             serial_number: None,
-            path_len: i32::MAX,
+            serial_allocator: None,
+            path_len: None,
+            basic_constraints_critical: None,
+            end_entity_basic_constraints: true,
             private_key: None,
+            public_key: None,
+            issuer_chain: None,
+            chain_composition: ChainComposition::Full,
+            permitted_names: Vec::new(),
+            excluded_names: Vec::new(),
+            policies: Vec::new(),
+            policy_mappings: Vec::new(),
+            policy_constraints: None,
+            inhibit_any_policy: None,
+            revocation_info: None,
+            extensions: Vec::new(),
+            removed_extensions: Vec::new(),
+            config: None,
+        }
+    }
+
+    /// Create a builder for renewal or re-keying of an existing certificate.
+    ///
+    /// Subject, alternative names, usage, basic constraints and other extensions are copied
+    /// from the certificate. The validity period starts now and keeps the original duration.
+    /// Serial number, key identifiers and issuer-specific extensions (CRL distribution points,
+    /// freshest CRL and authority information access) are generated anew.
+    /// A new private key is generated unless specified with [`CertificateBuilder::private_key`].
+    pub fn from_certificate(cert: &Certificate) -> Result<Self> {
+        let mut builder = Self::new();
+        builder.subject(CertName::from_der(&cert.subject_name().to_der()?)?);
+
+        let not_before = cert.not_before()?;
+        let not_after = cert.not_after()?;
+        builder.not_after = if not_after.is_no_expiry() {
+            not_after
+        } else {
+            builder
+                .not_before
+                .add_secs(not_after.unix_timestamp() - not_before.unix_timestamp())?
+        };
+
+        let mut usage = UsageProfile::new();
+        if let Some(key_usage) = cert.key_usage()? {
+            let critical = cert
+                .extension(OID_KEY_USAGE)
+                .is_some_and(|ext| ext.critical());
+            usage = usage.key_usage(key_usage).key_usage_critical(critical);
+        }
+        if let Some(oids) = cert.extended_key_usage()? {
+            let critical = cert
+                .extension(OID_EXTENDED_KEY_USAGE)
+                .is_some_and(|ext| ext.critical());
+            usage = oids
+                .iter()
+                .fold(usage, |usage, oid| usage.extended_usage(oid))
+                .extended_usage_critical(critical);
+        }
+        match cert.extension(OID_BASIC_CONSTRAINTS) {
+            Some(ext) => {
+                let (ca, path_len) = extension::decode_basic_constraints(ext.value())?;
+                usage = usage.ca(ca);
+                builder.path_len = path_len;
+                builder.basic_constraints_critical = Some(ext.critical());
+            }
+            None => builder.end_entity_basic_constraints = false,
+        }
+        builder.usage = usage;
+
+        let alt_names = cert_alt_names(cert);
+        let mut skipped = vec![
+            OID_BASIC_CONSTRAINTS,
+            OID_SUBJECT_KEY_IDENTIFIER,
+            OID_AUTHORITY_KEY_IDENTIFIER,
+            OID_KEY_USAGE,
+            OID_EXTENDED_KEY_USAGE,
+            OID_CRL_DISTRIBUTION_POINTS,
+            OID_FRESHEST_CRL,
+            OID_AUTHORITY_INFO_ACCESS,
+        ];
+        if let Some(ref alt_names) = alt_names {
+            builder.alt_names(alt_names);
+            skipped.push(OID_SUBJECT_ALT_NAME);
+        }
+        builder.extensions = cert
+            .extensions()
+            .into_iter()
+            .filter(|ext| !skipped.contains(&ext.oid()))
+            .collect();
+
+        Ok(builder)
+    }
+
+    /// Create a builder for renewal of the leaf certificate in the key store, keeping its private key.
+    /// See [`CertificateBuilder::from_certificate`] for details.
+    pub fn from_key_store(store: &KeyStore) -> Result<Self> {
+        let cert = store.certs().first().ok_or(PkiError::InvalidParameters)?;
+        let mut builder = Self::from_certificate(cert)?;
+        builder.private_key(store.private_key().clone());
+        Ok(builder)
+    }
+
+    /// Create a builder for the certificate requested by a PKCS#10 request.
+    ///
+    /// Subject, public key and subject alternative names are taken from the request,
+    /// other requested extensions are ignored. The request signature is verified.
+    /// Use [`CertificateBuilder::build_certificate`] to create the certificate.
+    pub fn from_request(request: &CertificateRequest) -> Result<Self> {
+        if !request.verify()? {
+            return Err(PkiError::InvalidParameters);
+        }
+        let mut builder = Self::new();
+        builder
+            .subject(CertName::from_der(&request.subject_name().to_der()?)?)
+            .public_key(request.public_key()?);
+        if let Some(alt_names) = request.extension(OID_SUBJECT_ALT_NAME) {
+            builder.extension(alt_names);
+        }
+        Ok(builder)
+    }
+
+    /// Create a builder for cross-certification of an existing CA certificate.
+    ///
+    /// In addition to the settings copied by [`CertificateBuilder::from_certificate`] the public key,
+    /// subject key identifier and validity period are kept intact. Specify the cross-signing CA
+    /// with [`CertificateBuilder::signer`] and either the private key of the certificate to
+    /// obtain a key store from [`CertificateBuilder::build`], or use
+    /// [`CertificateBuilder::build_certificate`] to create the certificate alone.
+    pub fn cross_certificate(cert: &Certificate) -> Result<Self> {
+        let mut builder = Self::from_certificate(cert)?;
+        builder
+            .public_key(cert.public_key()?)
+            .not_before(cert.not_before()?)
+            .not_after(cert.not_after()?);
+        if let Some(ski) = cert.extension(OID_SUBJECT_KEY_IDENTIFIER) {
+            builder.extension(ski);
         }
+        Ok(builder)
     }
 
     /// Specify certificate signer. If omitted or None a self-signed certificate is created.
@@ -63,17 +273,28 @@ impl<'a> CertificateBuilder<'a> {
     where
         S: Into<Option<&'a KeyStore>>,
     {
-        self.signer = signer.into();
+        self.signer = signer.into().map(|signer| signer as &dyn Signer);
         self
     }
 
-    /// Specify certificate usage
-    pub fn usage(&mut self, usage: CertUsage) -> &mut Self {
-        self.usage = usage;
+    /// Specify certificate signer implemented outside of this crate, for example a hardware key.
+    /// Replaces the signer set with [`CertificateBuilder::signer`].
+    pub fn external_signer(&mut self, signer: &'a dyn Signer) -> &mut Self {
+        self.signer = Some(signer);
         self
     }
 
-    /// Specify DNS or IP names for the subjectAltName extension.
+    /// Specify certificate usage, either one of the [`CertUsage`] presets
+    /// or a custom [`UsageProfile`]. Default is [`CertUsage::TlsServer`].
+    pub fn usage<U>(&mut self, usage: U) -> &mut Self
+    where
+        U: Into<UsageProfile>,
+    {
+        self.usage = usage.into();
+        self
+    }
+
+    /// Specify DNS names, IP addresses or email addresses for the subjectAltName extension.
     /// This is a required setting for the TLS SNI matching.
     pub fn alt_names<S, I>(&mut self, alt_names: I) -> &mut Self
     where
@@ -88,14 +309,21 @@ impl<'a> CertificateBuilder<'a> {
     }
 
     /// Specify start date of the certificate
-    pub fn not_before(&mut self, time: SystemTime) -> &mut Self {
-        self.not_before = time;
+    pub fn not_before<T>(&mut self, time: T) -> &mut Self
+    where
+        T: Into<CertTime>,
+    {
+        self.not_before = time.into();
         self
     }
 
-    /// Specify expiration date of the certificate
-    pub fn not_after(&mut self, time: SystemTime) -> &mut Self {
-        self.not_after = time;
+    /// Specify expiration date of the certificate.
+    /// Use [`CertTime::NO_EXPIRY`] for the certificates without a well-defined expiration date.
+    pub fn not_after<T>(&mut self, time: T) -> &mut Self
+    where
+        T: Into<CertTime>,
+    {
+        self.not_after = time.into();
         self
     }
 
@@ -106,30 +334,264 @@ impl<'a> CertificateBuilder<'a> {
         self
     }
 
+    /// Specify the public key of the certificate when the private key is not available.
+    /// If the private key is also specified it must match this public key.
+    pub fn public_key(&mut self, key: PublicKey) -> &mut Self {
+        self.public_key = Some(key);
+        self
+    }
+
+    /// Specify issuer certificates which are added after the new certificate into the key store.
+    /// By default the whole certificate chain of the signer is added.
+    pub fn issuer_chain<I>(&mut self, certs: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Certificate>,
+    {
+        self.issuer_chain = Some(certs.into_iter().collect());
+        self
+    }
+
+    /// Specify which certificates are included into the created key store, default is the full chain.
+    /// The root is detected by self-signature.
+    pub fn chain_composition(&mut self, composition: ChainComposition) -> &mut Self {
+        self.chain_composition = composition;
+        self
+    }
+
     /// Specify certificate subject
     pub fn subject(&mut self, name: CertName) -> &mut Self {
         self.subject = Some(name);
         self
     }
 
-    /// Specify serial number for the certificate, default is current Unix timestamp.
+    /// Specify serial number for the certificate, default is a random 16-octet number
+    /// with 126 bits of entropy, see [`SerialNumber::random`].
+    /// Zero is not a valid serial number, [`CertificateBuilder::build`] rejects it.
     pub fn serial_number(&mut self, number: u64) -> &mut Self {
-        self.serial_number = Some(number as u128);
+        self.serial_number = Some(SerialNumber::from_u128(number.into()));
+        self
+    }
+
+    /// Specify serial number of up to 20 octets for the certificate
+    pub fn serial<S>(&mut self, serial: S) -> &mut Self
+    where
+        S: Into<SerialNumber>,
+    {
+        self.serial_number = Some(serial.into());
+        self
+    }
+
+    /// Specify serial number allocator used when no explicit serial number is given.
+    /// Default is [`RandomSerialAllocator`].
+    pub fn serial_allocator(&mut self, allocator: &'a dyn SerialAllocator) -> &mut Self {
+        self.serial_allocator = Some(allocator);
+        self
+    }
+
+    /// Specify pathlen parameter for CA certificate, default is None which means unconstrained
+    pub fn path_len<P>(&mut self, path_len: P) -> &mut Self
+    where
+        P: Into<Option<u32>>,
+    {
+        self.path_len = path_len.into();
+        self
+    }
+
+    /// Specify criticality of the basic constraints extension.
+    /// Default is critical for CA and non-critical for end-entity certificates.
+    pub fn basic_constraints_critical(&mut self, critical: bool) -> &mut Self {
+        self.basic_constraints_critical = Some(critical);
+        self
+    }
+
+    /// Include basic constraints extension into end-entity certificates, default is true
+    pub fn end_entity_basic_constraints(&mut self, include: bool) -> &mut Self {
+        self.end_entity_basic_constraints = include;
+        self
+    }
+
+    /// Specify permitted subtrees of the name constraints extension.
+    /// Names in the certificates issued under this CA must fall into one of the subtrees of the same type.
+    pub fn permitted_names<I>(&mut self, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = NameConstraint>,
+    {
+        self.permitted_names = names.into_iter().collect();
+        self
+    }
+
+    /// Specify excluded subtrees of the name constraints extension.
+    /// Names in the certificates issued under this CA must not fall into any of the subtrees.
+    pub fn excluded_names<I>(&mut self, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = NameConstraint>,
+    {
+        self.excluded_names = names.into_iter().collect();
+        self
+    }
+
+    /// Specify certificate policies
+    pub fn policies<I>(&mut self, policies: I) -> &mut Self
+    where
+        I: IntoIterator<Item = CertificatePolicy>,
+    {
+        self.policies = policies.into_iter().collect();
+        self
+    }
+
+    /// Specify policy mappings of a CA certificate as pairs of issuer and subject domain policy OIDs
+    pub fn policy_mappings<S, I>(&mut self, mappings: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: AsRef<str>,
+    {
+        self.policy_mappings = mappings
+            .into_iter()
+            .map(|(issuer, subject)| (issuer.as_ref().to_owned(), subject.as_ref().to_owned()))
+            .collect();
+        self
+    }
+
+    /// Specify policy constraints of a CA certificate: the number of additional certificates
+    /// after which an explicit policy is required and policy mapping is inhibited
+    pub fn policy_constraints(
+        &mut self,
+        require_explicit_policy: Option<u32>,
+        inhibit_policy_mapping: Option<u32>,
+    ) -> &mut Self {
+        self.policy_constraints = Some((require_explicit_policy, inhibit_policy_mapping));
+        self
+    }
+
+    /// Specify the number of additional certificates after which anyPolicy is no longer accepted
+    pub fn inhibit_any_policy(&mut self, skip_certs: u32) -> &mut Self {
+        self.inhibit_any_policy = Some(skip_certs);
         self
     }
 
-    /// Specify pathlen parameter for CA certificate, default is i32::MAX
-    pub fn path_len(&mut self, path_len: i32) -> &mut Self {
-        self.path_len = path_len;
+    /// Specify CRL distribution points and authority information access locations.
+    /// If omitted, the default revocation information of the signer key store is used.
+    pub fn revocation_info(&mut self, info: RevocationInfo) -> &mut Self {
+        self.revocation_info = Some(info);
         self
     }
 
+    /// Add a custom extension. If the builder creates an extension with the same OID by default,
+    /// such as basic constraints, key identifiers or key usages, it is replaced in place.
+    pub fn extension(&mut self, extension: Extension) -> &mut Self {
+        self.removed_extensions.retain(|oid| oid != extension.oid());
+        match self
+            .extensions
+            .iter_mut()
+            .find(|ext| ext.oid() == extension.oid())
+        {
+            Some(ext) => *ext = extension,
+            None => self.extensions.push(extension),
+        }
+        self
+    }
+
+    /// Remove the extension with a given OID, either added by default or with [`Self::extension`]
+    pub fn remove_extension<S: AsRef<str>>(&mut self, oid: S) -> &mut Self {
+        let oid = oid.as_ref();
+        self.extensions.retain(|ext| ext.oid() != oid);
+        if !self.removed_extensions.iter().any(|removed| removed == oid) {
+            self.removed_extensions.push(oid.to_owned());
+        }
+        self
+    }
+
+    /// Add extensions defined in the OpenSSL x509v3 configuration syntax, see `man x509v3_config`.
+    /// The configuration consists either of `name = value` lines or of sections such as `[v3_req]`,
+    /// in which case the section name must be given. Sections referenced with `@section` are resolved
+    /// within the same configuration. Configured extensions replace the default ones with the same OID,
+    /// extensions added with [`Self::extension`] take precedence over the configured ones.
+    pub fn config_extensions<S: AsRef<str>>(
+        &mut self,
+        config: S,
+        section: Option<&str>,
+    ) -> &mut Self {
+        self.config = Some((config.as_ref().to_owned(), section.map(ToOwned::to_owned)));
+        self
+    }
+
+    /// Apply configured, custom and removed extensions to the default ones
+    fn merge_extensions(
+        &self,
+        defaults: Vec<Extension>,
+        configured: Vec<Extension>,
+    ) -> Vec<Extension> {
+        let mut custom = configured
+            .iter()
+            .filter(|ext| {
+                !self.extensions.iter().any(|e| e.oid() == ext.oid())
+                    && !self.removed_extensions.iter().any(|oid| oid == ext.oid())
+            })
+            .chain(self.extensions.iter())
+            .collect::<Vec<_>>();
+        let mut result = Vec::new();
+        for ext in defaults {
+            if self.removed_extensions.iter().any(|oid| oid == ext.oid()) {
+                continue;
+            }
+            match custom.iter().position(|c| c.oid() == ext.oid()) {
+                Some(pos) => result.push(custom.remove(pos).clone()),
+                None => result.push(ext),
+            }
+        }
+        result.extend(custom.into_iter().cloned());
+        result
+    }
+
     /// Create X.509 certificate chain
     pub fn build(&self) -> Result<KeyStore> {
-        let cert_key = match self.private_key {
-            Some(ref private_key) => private_key.clone(),
-            None => PrivateKey::new_rsa(DEFAULT_RSA_KEY_LENGTH)?,
+        let cert_key = match (&self.private_key, &self.public_key) {
+            (Some(private_key), _) => private_key.clone(),
+            (None, Some(_)) => return Err(PkiError::MissingPrivateKey),
+            (None, None) => PrivateKey::new_rsa(DEFAULT_RSA_KEY_LENGTH)?,
+        };
+
+        let issuer_chain = match (&self.issuer_chain, self.signer) {
+            (Some(chain), _) => chain.as_slice(),
+            (None, Some(signer)) => signer.certs(),
+            (None, None) => &[],
         };
+        let mut certs = vec![self.build_x509(Some(&cert_key))?];
+        match self.chain_composition {
+            ChainComposition::LeafOnly => {}
+            ChainComposition::WithoutRoot => certs.extend(
+                issuer_chain
+                    .iter()
+                    .filter(|cert| !cert.is_self_signed())
+                    .cloned(),
+            ),
+            ChainComposition::Full => certs.extend(issuer_chain.iter().cloned()),
+        }
+
+        KeyStore::new(cert_key, certs)
+    }
+
+    /// Create a single X.509 certificate without the private key.
+    /// Either the public or the private key must be specified
+    /// unless a self-signed certificate is created for the signer key.
+    pub fn build_certificate(&self) -> Result<Certificate> {
+        self.build_x509(self.private_key.as_ref())
+    }
+
+    fn build_x509(&self, private_key: Option<&PrivateKey>) -> Result<Certificate> {
+        let public_key = match (&self.public_key, private_key) {
+            (Some(public_key), Some(private_key)) if *public_key != private_key.public_key()? => {
+                return Err(PkiError::InvalidParameters)
+            }
+            (Some(public_key), _) => public_key.clone(),
+            (None, Some(private_key)) => private_key.public_key()?,
+            (None, None) => match self.signer {
+                // self-signed certificate for an external key
+                Some(signer) if signer.certs().is_empty() => signer.public_key()?,
+                _ => return Err(PkiError::InvalidParameters),
+            },
+        };
+        let issuer_cert = self.signer.and_then(|signer| signer.certs().first());
 
         let empty_name: CertName;
         let subject = match self.subject.as_ref() {
@@ -141,143 +603,327 @@ impl<'a> CertificateBuilder<'a> {
         };
 
         let mut builder = X509::builder()?;
-        builder.set_pubkey(&cert_key.0)?;
+        builder.set_pubkey(&public_key.0)?;
         builder.set_version(2)?;
         builder.set_issuer_name(
-            self.signer
-                .map(|s| s.certs()[0].subject_name().into())
+            issuer_cert
+                .map(|cert| cert.subject_name().into())
                 .unwrap_or(subject),
         )?;
-        builder.set_not_before(
-            Asn1Time::from_unix(
-                self.not_before
-                    .duration_since(SystemTime::UNIX_EPOCH)?
-                    .as_secs() as _,
-            )?
-            .as_ref(),
-        )?;
-        builder.set_not_after(
-            Asn1Time::from_unix(
-                self.not_after
-                    .duration_since(SystemTime::UNIX_EPOCH)?
-                    .as_secs() as _,
-            )?
-            .as_ref(),
-        )?;
+        builder.set_not_before(self.not_before.to_asn1_time()?.as_ref())?;
+        builder.set_not_after(self.not_after.to_asn1_time()?.as_ref())?;
         builder.set_subject_name(subject)?;
 
         let serial_number = match self.serial_number {
-            Some(number) => number.to_be_bytes(),
-            None => SystemTime::UNIX_EPOCH.elapsed()?.as_millis().to_be_bytes(),
+            Some(ref number) => number.clone(),
+            None => self
+                .serial_allocator
+                .unwrap_or(&RandomSerialAllocator)
+                .allocate()?,
         };
-        let asn_number = Asn1Integer::from_bn(BigNum::from_slice(&serial_number)?.as_ref())?;
-        builder.set_serial_number(&asn_number)?;
+        if serial_number.is_zero() {
+            return Err(PkiError::InvalidParameters);
+        }
+        builder.set_serial_number(serial_number.to_asn1_integer()?.as_ref())?;
 
         // man x509v3_config
-        let mut basic_cons = x509::extension::BasicConstraints::new();
-
-        if self.usage == CertUsage::CA {
-            basic_cons.ca().critical().pathlen(self.path_len as _);
+        let mut extensions = Vec::new();
+        let ca = self.usage.is_ca();
+        if ca || self.end_entity_basic_constraints {
+            let path_len = if ca { self.path_len } else { None };
+            extensions.push(Extension::new(
+                OID_BASIC_CONSTRAINTS,
+                self.basic_constraints_critical.unwrap_or(ca),
+                extension::basic_constraints(ca, path_len),
+            ));
         }
-        builder.append_extension(basic_cons.build()?)?;
 
         let subj_key = x509::extension::SubjectKeyIdentifier::new()
             .build(&builder.x509v3_context(None, None))?;
+        let subj_key = Extension::from_openssl(&subj_key);
 
-        builder.append_extension(subj_key)?;
+        let auth_key_id = match issuer_cert {
+            Some(cert) => extension::cert_extension(&cert.0, OID_SUBJECT_KEY_IDENTIFIER),
+            None => Some(
+                self.extensions
+                    .iter()
+                    .find(|ext| ext.oid() == OID_SUBJECT_KEY_IDENTIFIER)
+                    .unwrap_or(&subj_key)
+                    .clone(),
+            ),
+        };
+        extensions.push(subj_key);
 
-        let auth_key = x509::extension::AuthorityKeyIdentifier::new()
-            .keyid(false)
-            .issuer(false)
-            .build(&builder.x509v3_context(self.signer.map(|s| s.certs()[0].0.as_ref()), None))?;
+        if let Some(key_id) = auth_key_id {
+            let value = extension::authority_key_identifier(key_id.value())?;
+            extensions.push(Extension::new(OID_AUTHORITY_KEY_IDENTIFIER, false, value));
+        }
 
-        builder.append_extension(auth_key)?;
+        if !self.usage.extended_usages().is_empty() {
+            let value = extension::extended_key_usage_value(self.usage.extended_usages())?;
+            extensions.push(Extension::new(
+                OID_EXTENDED_KEY_USAGE,
+                self.usage.is_extended_usage_critical(),
+                value,
+            ));
+        }
 
-        if self.usage != CertUsage::CA {
-            let mut extended_key_usage = x509::extension::ExtendedKeyUsage::new();
-            match self.usage {
-                CertUsage::TlsServer => {
-                    extended_key_usage.server_auth();
-                }
-                CertUsage::TlsClient => {
-                    extended_key_usage.client_auth();
-                }
-                CertUsage::CodeSign => {
-                    extended_key_usage.code_signing();
-                }
-                CertUsage::TlsServerAndClient => {
-                    extended_key_usage.server_auth().client_auth();
-                }
-                _ => {}
-            }
-            builder.append_extension(extended_key_usage.build()?)?;
+        if !self.usage.key_usages().is_empty() {
+            extensions.push(Extension::new(
+                OID_KEY_USAGE,
+                self.usage.is_key_usage_critical(),
+                self.usage.key_usages().encode(),
+            ));
         }
 
-        let mut key_usage = x509::extension::KeyUsage::new();
-        match self.usage {
-            CertUsage::CA => {
-                key_usage.key_cert_sign().crl_sign();
-            }
-            CertUsage::TlsServer | CertUsage::TlsClient | CertUsage::TlsServerAndClient => {
-                key_usage
-                    .digital_signature()
-                    .non_repudiation()
-                    .key_encipherment()
-                    .data_encipherment();
-            }
-            CertUsage::CodeSign => {
-                key_usage.digital_signature().non_repudiation();
-            }
+        if !self.permitted_names.is_empty() || !self.excluded_names.is_empty() {
+            let value = extension::name_constraints(&self.permitted_names, &self.excluded_names);
+            extensions.push(Extension::new(OID_NAME_CONSTRAINTS, true, value));
+        }
+
+        if !self.policies.is_empty() {
+            let value = extension::certificate_policies(&self.policies)?;
+            extensions.push(Extension::new(OID_CERTIFICATE_POLICIES, false, value));
+        }
+
+        if !self.policy_mappings.is_empty() {
+            let value = extension::policy_mappings(&self.policy_mappings)?;
+            extensions.push(Extension::new(OID_POLICY_MAPPINGS, true, value));
+        }
+
+        if let Some((require_explicit_policy, inhibit_policy_mapping)) = self.policy_constraints {
+            let value =
+                extension::policy_constraints(require_explicit_policy, inhibit_policy_mapping);
+            extensions.push(Extension::new(OID_POLICY_CONSTRAINTS, true, value));
+        }
+
+        if let Some(skip_certs) = self.inhibit_any_policy {
+            let value = extension::inhibit_any_policy(skip_certs);
+            extensions.push(Extension::new(OID_INHIBIT_ANY_POLICY, true, value));
+        }
+
+        let revocation_info = self
+            .revocation_info
+            .as_ref()
+            .or_else(|| self.signer.and_then(|signer| signer.revocation_info()));
+        if let Some(info) = revocation_info {
+            extensions.extend(info.extensions()?);
         }
-        builder.append_extension(key_usage.build()?)?;
 
         if !self.alt_names.is_empty() {
-            let mut subj_alt_name = x509::extension::SubjectAlternativeName::new();
-            for name in &self.alt_names {
-                if name.parse::<Ipv4Addr>().is_ok() {
-                    subj_alt_name.ip(name);
-                } else {
-                    subj_alt_name.dns(name);
-                }
-            }
-            builder.append_extension(subj_alt_name.build(&builder.x509v3_context(None, None))?)?;
+            let context = builder.x509v3_context(None, None);
+            extensions.push(extension::subject_alt_name(&self.alt_names, &context)?);
         }
 
-        if let Some(signer) = self.signer {
-            builder.sign(&signer.private_key().0, MessageDigest::sha256())?;
-        } else {
-            builder.sign(&cert_key.0, MessageDigest::sha256())?;
+        let configured = match self.config {
+            Some((ref config, ref section)) => extension::config_extensions(
+                &builder,
+                issuer_cert.map(|cert| cert.0.as_ref()),
+                config,
+                section.as_deref(),
+            )?,
+            None => Vec::new(),
+        };
+
+        for ext in self.merge_extensions(extensions, configured) {
+            builder.append_extension(ext.to_openssl()?)?;
         }
 
-        let mut certs: Vec<Certificate> = vec![builder.build().into()];
-        if let Some(signer) = self.signer {
-            for cert in signer.certs() {
-                certs.push(cert.clone());
+        let signer = match (self.signer, private_key) {
+            (Some(signer), _) => signer,
+            (None, Some(private_key)) => private_key as &dyn Signer,
+            (None, None) => return Err(PkiError::MissingPrivateKey),
+        };
+
+        let algorithm = signer.algorithm()?;
+        let tbs = signer::tbs_certificate(&builder.build(), algorithm)?;
+        let signature = signer.sign(&tbs)?;
+
+        let cert = Certificate::from_der(&signer::signed_data(tbs, algorithm, signature)?)?;
+
+        if !cert.0.verify(&signer.public_key()?.0)? {
+            return Err(PkiError::InvalidParameters);
+        }
+        Ok(cert)
+    }
+}
+
+/// CA key rollover certificates as defined in RFC 4210, section 4.4
+pub struct KeyRollover {
+    old_with_new: KeyStore,
+    new_with_old: KeyStore,
+}
+
+impl KeyRollover {
+    /// Create rollover certificates between the old and the new self-signed CA certificates.
+    /// The validity period of the NewWithOld certificate ends with the old CA certificate.
+    pub fn new(old_ca: &KeyStore, new_ca: &KeyStore) -> Result<Self> {
+        let old_cert = old_ca.certs().first().ok_or(PkiError::InvalidParameters)?;
+        let new_cert = new_ca.certs().first().ok_or(PkiError::InvalidParameters)?;
+
+        let old_with_new = CertificateBuilder::cross_certificate(old_cert)?
+            .signer(new_ca)
+            .private_key(old_ca.private_key().clone())
+            .build()?;
+
+        let new_with_old = CertificateBuilder::cross_certificate(new_cert)?
+            .signer(old_ca)
+            .private_key(new_ca.private_key().clone())
+            .not_after(new_cert.not_after()?.min(old_cert.not_after()?))
+            .build()?;
+
+        Ok(Self {
+            old_with_new,
+            new_with_old,
+        })
+    }
+
+    /// Old CA public key signed with the new CA private key, followed by the new CA certificate.
+    /// Allows relying parties which trust the new CA to verify the chains issued by the old CA.
+    pub fn old_with_new(&self) -> &KeyStore {
+        &self.old_with_new
+    }
+
+    /// New CA public key signed with the old CA private key, followed by the old CA certificate.
+    /// Allows relying parties which trust the old CA to verify the chains issued by the new CA.
+    pub fn new_with_old(&self) -> &KeyStore {
+        &self.new_with_old
+    }
+}
+
+/// Whether the basic constraints extension of the certificate has the CA flag set
+fn is_ca_cert(cert: &Certificate) -> bool {
+    cert.extension(OID_BASIC_CONSTRAINTS)
+        .and_then(|ext| extension::decode_basic_constraints(ext.value()).ok())
+        .is_some_and(|(ca, _)| ca)
+}
+
+/// Alternative names of the certificate if all of them are DNS names, IP or email addresses
+fn cert_alt_names(cert: &Certificate) -> Option<Vec<String>> {
+    let names = match cert.0.subject_alt_names() {
+        Some(names) => names,
+        None => return Some(Vec::new()),
+    };
+    names
+        .iter()
+        .map(|name| {
+            if let Some(dns) = name.dnsname() {
+                Some(dns.to_owned())
+            } else if let Some(email) = name.email() {
+                Some(email.to_owned())
+            } else {
+                let ip = name.ipaddress()?;
+                match ip.len() {
+                    4 => Some(IpAddr::from(<[u8; 4]>::try_from(ip).ok()?).to_string()),
+                    16 => Some(IpAddr::from(<[u8; 16]>::try_from(ip).ok()?).to_string()),
+                    _ => None,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Peer identity which the leaf certificate must be valid for
+#[derive(Clone, Copy)]
+enum PeerIdentity<'b> {
+    Host(&'b str),
+    Ip(IpAddr),
+    Email(&'b str),
+}
+
+/// Purpose which the verified certificate chain must be valid for
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyPurpose {
+    /// Predefined certificate usage
+    Usage(CertUsage),
+    /// Custom extended key usage OIDs, all of which must be allowed
+    ExtendedUsage(Vec<String>),
+}
+
+impl VerifyPurpose {
+    /// Create a purpose from a list of extended key usage OIDs
+    pub fn extended_usage<S, I>(oids: I) -> Self
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = S>,
+    {
+        Self::ExtendedUsage(oids.into_iter().map(|s| s.as_ref().to_owned()).collect())
+    }
+
+    /// OpenSSL purposes checked for the certificate usage, they cover the key usage,
+    /// extended key usage and basic constraints of every certificate in the chain
+    fn openssl_purposes(&self) -> &'static [X509PurposeId] {
+        match self {
+            Self::Usage(CertUsage::TlsServer) => &[X509PurposeId::SSL_SERVER],
+            Self::Usage(CertUsage::TlsClient) => &[X509PurposeId::SSL_CLIENT],
+            Self::Usage(CertUsage::TlsServerAndClient) => {
+                &[X509PurposeId::SSL_SERVER, X509PurposeId::SSL_CLIENT]
             }
+            _ => &[],
         }
+    }
 
-        KeyStore::new(cert_key, certs)
+    /// Extended key usage OIDs checked manually when there is no matching OpenSSL purpose
+    fn extended_usage_oids(&self) -> Vec<&str> {
+        match self {
+            Self::Usage(usage) => usage.extended_usage_oids().to_vec(),
+            Self::ExtendedUsage(oids) => oids.iter().map(|s| s.as_str()).collect(),
+        }
+    }
+}
+
+impl From<CertUsage> for VerifyPurpose {
+    fn from(usage: CertUsage) -> Self {
+        Self::Usage(usage)
+    }
+}
+
+impl fmt::Display for VerifyPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Usage(usage) => write!(f, "{:?}", usage),
+            Self::ExtendedUsage(oids) => write!(f, "{}", oids.join(",")),
+        }
     }
 }
 
 /// Certificate chain verifier
-pub struct CertificateVerifier<'a> {
-    roots: Vec<&'a Certificate>,
+pub struct CertificateVerifier {
+    roots: Vec<Certificate>,
+    root_dirs: Vec<String>,
     default_paths: bool,
+    partial_chain: bool,
+    cn_fallback: bool,
+    partial_wildcards: bool,
+    verification_time: Option<CertTime>,
+    clock_skew: Duration,
+    purpose: Option<VerifyPurpose>,
+    policies: Vec<String>,
+    policy_flags: X509VerifyFlags,
 }
 
-impl<'a> Default for CertificateVerifier<'a> {
+impl Default for CertificateVerifier {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<'a> CertificateVerifier<'a> {
+impl CertificateVerifier {
     /// Create new verifier instance
     pub fn new() -> Self {
         Self {
             roots: Vec::new(),
+            root_dirs: Vec::new(),
             default_paths: true,
+            partial_chain: false,
+            cn_fallback: false,
+            partial_wildcards: false,
+            verification_time: None,
+            clock_skew: Duration::ZERO,
+            purpose: None,
+            policies: Vec::new(),
+            policy_flags: X509VerifyFlags::empty(),
         }
     }
 
@@ -288,13 +934,364 @@ impl<'a> CertificateVerifier<'a> {
     }
 
     /// Specify a custom CA root certificate
-    pub fn ca_root(&mut self, root: &'a Certificate) -> &mut Self {
-        self.roots.push(root);
+    pub fn ca_root(&mut self, root: &Certificate) -> &mut Self {
+        self.roots.push(root.clone());
+        self
+    }
+
+    /// Add all certificates from a PEM bundle as trust anchors
+    pub fn ca_bundle(&mut self, pem: &[u8]) -> Result<&mut Self> {
+        let certs = X509::stack_from_pem(pem)?;
+        if certs.is_empty() {
+            return Err(PkiError::InvalidParameters);
+        }
+        self.roots.extend(certs.into_iter().map(Certificate));
+        Ok(self)
+    }
+
+    /// Add all certificates from a PEM bundle file as trust anchors
+    pub fn ca_bundle_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self> {
+        self.ca_bundle(&fs::read(path)?)
+    }
+
+    /// Add a directory of trust anchors in the OpenSSL hashed format, as created by `c_rehash`.
+    /// Certificates are looked up on demand during the verification.
+    /// The path must be valid UTF-8.
+    pub fn ca_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self> {
+        let dir = path.as_ref().to_str().ok_or(PkiError::InvalidParameters)?;
+        self.root_dirs.push(dir.to_owned());
+        Ok(self)
+    }
+
+    /// Add the CA certificates from the key store chain as trust anchors.
+    /// Certificates without the CA flag in the basic constraints, such as a leaf certificate, are skipped.
+    pub fn ca_key_store(&mut self, store: &KeyStore) -> &mut Self {
+        self.roots.extend(
+            store
+                .certs()
+                .iter()
+                .filter(|cert| is_ca_cert(cert))
+                .cloned(),
+        );
+        self
+    }
+
+    /// Accept chains which end in a trusted intermediate certificate instead of a self-signed root,
+    /// default is false
+    pub fn partial_chain(&mut self, flag: bool) -> &mut Self {
+        self.partial_chain = flag;
+        self
+    }
+
+    /// Fall back to the subject common name for hostname and email matching
+    /// when the leaf certificate has no subjectAltName of that type, default is false
+    pub fn cn_fallback(&mut self, flag: bool) -> &mut Self {
+        self.cn_fallback = flag;
+        self
+    }
+
+    /// Allow partial wildcards such as `api*.example.com` in hostname matching, default is false.
+    /// Full-label wildcards in the left-most label such as `*.example.com` are always allowed.
+    pub fn partial_wildcards(&mut self, flag: bool) -> &mut Self {
+        self.partial_wildcards = flag;
+        self
+    }
+
+    /// Validate certificates at a given point in time instead of the current time
+    pub fn verification_time<T>(&mut self, time: T) -> &mut Self
+    where
+        T: Into<CertTime>,
+    {
+        self.verification_time = Some(time.into());
+        self
+    }
+
+    /// Specify clock skew tolerance, default is zero.
+    /// Certificates which become valid or expire within the tolerance of the verification time are accepted.
+    /// Note that a non-zero tolerance disables OpenSSL's own time checks, including CRL validity periods.
+    pub fn clock_skew(&mut self, skew: Duration) -> &mut Self {
+        self.clock_skew = skew;
+        self
+    }
+
+    /// Require the chain to be valid for a given purpose, default is none.
+    /// TLS usages are checked with the OpenSSL purposes, which also select the trust settings
+    /// and require a matching key usage. For other purposes every certificate in the chain
+    /// which has an extended key usage extension must allow the purpose, a code signing leaf
+    /// must allow digital signatures and for [`CertUsage::CA`] the leaf certificate must be
+    /// a CA allowed to sign certificates.
+    pub fn purpose<P: Into<VerifyPurpose>>(&mut self, purpose: P) -> &mut Self {
+        self.purpose = Some(purpose.into());
+        self
+    }
+
+    /// Specify the set of policy OIDs acceptable to the caller and enable policy checking.
+    /// The resulting policy tree is available in the [`VerifyReport`].
+    pub fn acceptable_policies<S, I>(&mut self, policies: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.policies = policies
+            .into_iter()
+            .map(|oid| oid.as_ref().to_owned())
+            .collect();
+        self.policy_flags |= X509VerifyFlags::POLICY_CHECK;
+        self
+    }
+
+    /// Require the chain to be valid for at least one acceptable policy, default is false
+    pub fn explicit_policy(&mut self, flag: bool) -> &mut Self {
+        self.set_policy_flag(X509VerifyFlags::EXPLICIT_POLICY, flag)
+    }
+
+    /// Do not accept anyPolicy as a match for the acceptable policies, default is false
+    pub fn inhibit_any_policy(&mut self, flag: bool) -> &mut Self {
+        self.set_policy_flag(X509VerifyFlags::INHIBIT_ANY, flag)
+    }
+
+    /// Do not allow policy mappings, default is false
+    pub fn inhibit_policy_mapping(&mut self, flag: bool) -> &mut Self {
+        self.set_policy_flag(X509VerifyFlags::INHIBIT_MAP, flag)
+    }
+
+    fn set_policy_flag(&mut self, flag: X509VerifyFlags, value: bool) -> &mut Self {
+        self.policy_flags.set(flag, value);
+        if value {
+            self.policy_flags |= X509VerifyFlags::POLICY_CHECK;
+        }
         self
     }
 
     /// Verify a given certificate chain. The first element in the chain must be a leaf certificate.
     pub fn verify(&self, chain: &[Certificate]) -> Result<()> {
+        self.verify_chain(chain, None)
+    }
+
+    /// Verify a given certificate chain and check that the leaf certificate is valid for a given hostname.
+    /// Wildcard names are matched according to RFC 6125.
+    pub fn verify_for_host(&self, chain: &[Certificate], host: &str) -> Result<()> {
+        self.verify_chain(chain, Some(PeerIdentity::Host(host)))
+    }
+
+    /// Verify a given certificate chain and check that the leaf certificate is valid for a given IP address
+    pub fn verify_for_ip(&self, chain: &[Certificate], ip: IpAddr) -> Result<()> {
+        self.verify_chain(chain, Some(PeerIdentity::Ip(ip)))
+    }
+
+    /// Verify a given certificate chain and check that the leaf certificate is valid for a given email address
+    pub fn verify_for_email(&self, chain: &[Certificate], email: &str) -> Result<()> {
+        self.verify_chain(chain, Some(PeerIdentity::Email(email)))
+    }
+
+    /// Verify a given certificate chain and return a report with every failure found
+    /// and the certificate path built by the verifier
+    pub fn verify_detailed(&self, chain: &[Certificate]) -> Result<VerifyReport> {
+        self.verify_report(chain, None)
+    }
+
+    /// Verify a leaf certificate using an unordered set of intermediate certificates,
+    /// which may contain duplicates, unrelated or alternative cross-signed certificates.
+    /// Candidate paths are built from the intermediates and the first one which verifies successfully
+    /// is returned, starting with the leaf certificate and ending with the trust anchor.
+    pub fn verify_with_intermediates(
+        &self,
+        leaf: &Certificate,
+        intermediates: &[Certificate],
+    ) -> Result<Vec<Certificate>> {
+        let mut seen = Vec::new();
+        let mut pool = Vec::new();
+        for cert in intermediates {
+            let der = cert.to_der()?;
+            if !seen.contains(&der) {
+                seen.push(der);
+                pool.push(cert);
+            }
+        }
+
+        let mut chain = vec![leaf.clone()];
+        chain.extend(pool.iter().map(|cert| (*cert).clone()));
+        let error = match self.verify_report(&chain, None)?.into_result() {
+            Ok(path) => return Ok(path),
+            Err(error) => error,
+        };
+
+        for candidate in candidate_paths(leaf, &pool) {
+            let mut chain = vec![leaf.clone()];
+            chain.extend(candidate.into_iter().cloned());
+            if let Ok(path) = self.verify_report(&chain, None)?.into_result() {
+                return Ok(path);
+            }
+        }
+        Err(error)
+    }
+
+    fn verify_param(&self, identity: Option<&PeerIdentity>) -> Result<X509VerifyParam> {
+        let mut param = X509VerifyParam::new()?;
+        if let Some(purpose) = self
+            .purpose
+            .as_ref()
+            .and_then(|purpose| purpose.openssl_purposes().first())
+        {
+            // the trust settings are derived from the purpose
+            param.set_purpose(*purpose)?;
+        }
+        let mut host_flags = X509CheckFlags::empty();
+        if !self.cn_fallback {
+            host_flags |= X509CheckFlags::NEVER_CHECK_SUBJECT;
+        }
+        if !self.partial_wildcards {
+            host_flags |= X509CheckFlags::NO_PARTIAL_WILDCARDS;
+        }
+        param.set_hostflags(host_flags);
+        if self.partial_chain {
+            param.set_flags(X509VerifyFlags::PARTIAL_CHAIN)?;
+        }
+        if !self.policy_flags.is_empty() {
+            param.set_flags(self.policy_flags)?;
+        }
+        if !self.policies.is_empty() {
+            let mut policies = Stack::new()?;
+            for oid in &self.policies {
+                policies.push(Asn1Object::from_str(oid)?)?;
+            }
+            // SAFETY: the policies are copied into the parameters
+            let result =
+                unsafe { ffi::X509_VERIFY_PARAM_set1_policies(param.as_ptr(), policies.as_ptr()) };
+            if result <= 0 {
+                return Err(ErrorStack::get().into());
+            }
+        }
+        if !self.clock_skew.is_zero() {
+            // validity periods are checked separately with the tolerance applied
+            param.set_flags(X509VerifyFlags::NO_CHECK_TIME)?;
+        } else {
+            // OpenSSL reads the coarse system clock which may lag behind the one
+            // used for the default validity period of the issued certificates
+            let time = self.verification_time.unwrap_or_else(CertTime::now);
+            param.set_time(time.unix_timestamp() as _);
+        }
+        match identity {
+            Some(PeerIdentity::Host(host)) => param.set_host(host)?,
+            Some(PeerIdentity::Ip(ip)) => param.set_ip(*ip)?,
+            Some(PeerIdentity::Email(email)) => param.set_email(email)?,
+            None => {}
+        }
+        Ok(param)
+    }
+
+    fn check_validity_period(
+        &self,
+        path: &[X509],
+        failures: &mut Vec<VerifyFailure>,
+    ) -> Result<()> {
+        let now = self
+            .verification_time
+            .unwrap_or_else(CertTime::now)
+            .unix_timestamp();
+        // the bounds are clamped to the range which can be encoded in the certificate
+        let skew = i64::try_from(self.clock_skew.as_secs()).unwrap_or(i64::MAX);
+        let latest_start = now
+            .saturating_add(skew)
+            .min(CertTime::NO_EXPIRY.unix_timestamp());
+        let earliest_end = now.saturating_sub(skew).max(CertTime::MIN.unix_timestamp());
+        let latest_start = Asn1Time::from_unix(latest_start as _)?;
+        let earliest_end = Asn1Time::from_unix(earliest_end as _)?;
+        for (depth, cert) in path.iter().enumerate() {
+            let error = if cert.not_before() > latest_start {
+                X509_V_ERR_CERT_NOT_YET_VALID
+            } else if cert.not_after() < earliest_end {
+                X509_V_ERR_CERT_HAS_EXPIRED
+            } else {
+                continue;
+            };
+            // SAFETY: the error code is a valid X509_V_ERR constant
+            let error = unsafe { X509VerifyResult::from_raw(error) };
+            failures.push(VerifyFailure::new(
+                depth,
+                Some(cert),
+                PkiError::Verify(error),
+            ));
+        }
+        Ok(())
+    }
+
+    fn purpose_mismatch(&self, depth: usize, cert: &X509Ref) -> PkiError {
+        PkiError::PurposeMismatch {
+            depth,
+            subject: CertNameRef(cert.subject_name()).to_string(),
+            purpose: self
+                .purpose
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn check_purpose(&self, path: &[X509], failures: &mut Vec<VerifyFailure>) -> Result<()> {
+        let purpose = match self.purpose {
+            Some(ref purpose) => purpose,
+            None => return Ok(()),
+        };
+        let mut mismatch = |depth: usize, cert: &X509| {
+            let error = self.purpose_mismatch(depth, cert);
+            failures.push(VerifyFailure::new(depth, Some(cert), error));
+        };
+
+        let openssl_purposes = purpose.openssl_purposes();
+        if !openssl_purposes.is_empty() {
+            // the first purpose is checked by OpenSSL during the verification
+            for (depth, cert) in path.iter().enumerate() {
+                let allowed = openssl_purposes[1..].iter().all(|id| {
+                    let ca = c_int::from(depth > 0);
+                    // SAFETY: the certificate pointer is valid, the cached extensions are updated
+                    let result = unsafe { ffi::X509_check_purpose(cert.as_ptr(), id.as_raw(), ca) };
+                    result == 1
+                });
+                if !allowed {
+                    mismatch(depth, cert);
+                }
+            }
+            return Ok(());
+        }
+
+        let required = purpose.extended_usage_oids();
+        for (depth, cert) in path.iter().enumerate() {
+            if let Some(oids) = extension::extended_key_usage(cert)? {
+                let allowed = oids.iter().any(|oid| oid == OID_ANY_EXTENDED_KEY_USAGE)
+                    || required.iter().all(|r| oids.iter().any(|oid| oid == r));
+                if !allowed {
+                    mismatch(depth, cert);
+                }
+            }
+        }
+        if let (VerifyPurpose::Usage(usage), Some(leaf)) = (purpose, path.first()) {
+            // SAFETY: the certificate pointer is valid
+            let (flags, key_usage) = unsafe {
+                (
+                    openssl_sys::X509_get_extension_flags(leaf.as_ptr()),
+                    openssl_sys::X509_get_key_usage(leaf.as_ptr()),
+                )
+            };
+            let allowed = match usage {
+                CertUsage::CA => {
+                    flags & openssl_sys::EXFLAG_CA != 0 && key_usage & KU_KEY_CERT_SIGN != 0
+                }
+                CertUsage::CodeSign => key_usage & KU_DIGITAL_SIGNATURE != 0,
+                _ => true,
+            };
+            if !allowed {
+                mismatch(0, leaf);
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_report(
+        &self,
+        chain: &[Certificate],
+        identity: Option<PeerIdentity>,
+    ) -> Result<VerifyReport> {
         if chain.is_empty() {
             return Err(PkiError::InvalidParameters);
         }
@@ -306,6 +1303,14 @@ impl<'a> CertificateVerifier<'a> {
         for root in &self.roots {
             store_builder.add_cert(root.0.clone())?;
         }
+        if !self.root_dirs.is_empty() {
+            let lookup = store_builder.add_lookup(X509Lookup::hash_dir())?;
+            for dir in &self.root_dirs {
+                lookup.add_dir(dir, SslFiletype::PEM)?;
+            }
+        }
+        let param = self.verify_param(identity.as_ref())?;
+        store_builder.set_param(&param)?;
         let store = store_builder.build();
 
         let mut context = X509StoreContext::new()?;
@@ -315,12 +1320,305 @@ impl<'a> CertificateVerifier<'a> {
             stack.push(cert.0.clone())?;
         }
 
-        let result = context.init(&store, &chain[0].0, &stack, |context| context.verify_cert())?;
+        let (errors, path, policy_tree) = context.init(&store, &chain[0].0, &stack, |context| {
+            VERIFY_ERRORS.with(|errors| errors.borrow_mut().clear());
+            // SAFETY: the context is initialized and the callback does not outlive it
+            unsafe {
+                ffi::X509_STORE_CTX_set_verify_cb(context.as_ptr(), Some(collect_verify_error));
+            }
+            let result = context.verify_cert();
+            let mut errors = VERIFY_ERRORS.with(|errors| errors.take());
+            if !result? && errors.is_empty() {
+                errors.push((
+                    context.error(),
+                    context.error_depth() as usize,
+                    context.current_cert().map(|cert| cert.to_owned()),
+                ));
+            }
+            let path = context
+                .chain()
+                .map(|chain| chain.iter().map(|cert| cert.to_owned()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            Ok((errors, path, policy_tree(context)))
+        })?;
 
-        if !result {
-            Err(PkiError::Verify(context.error()))
-        } else {
-            Ok(())
+        let mut failures = errors
+            .into_iter()
+            .map(|(error, depth, cert)| VerifyFailure {
+                depth,
+                error: match cert {
+                    Some(ref cert) if error.as_raw() == X509_V_ERR_INVALID_PURPOSE => {
+                        self.purpose_mismatch(depth, cert)
+                    }
+                    _ => map_verify_error(error, identity),
+                },
+                certificate: cert.map(Certificate),
+            })
+            .collect::<Vec<_>>();
+        if !self.clock_skew.is_zero() {
+            self.check_validity_period(&path, &mut failures)?;
+        }
+        self.check_purpose(&path, &mut failures)?;
+        // purpose mismatches found by OpenSSL are reported after the other errors
+        failures.sort_by_key(|failure| matches!(failure.error, PkiError::PurposeMismatch { .. }));
+
+        Ok(VerifyReport {
+            failures,
+            path: path.into_iter().map(Certificate).collect(),
+            policy_tree,
+        })
+    }
+
+    fn verify_chain(&self, chain: &[Certificate], identity: Option<PeerIdentity>) -> Result<()> {
+        match self
+            .verify_report(chain, identity)?
+            .failures
+            .into_iter()
+            .next()
+        {
+            Some(failure) => Err(failure.error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Build candidate issuer paths for a given certificate, each path excludes the certificate itself
+fn candidate_paths<'c>(
+    cert: &'c Certificate,
+    pool: &[&'c Certificate],
+) -> Vec<Vec<&'c Certificate>> {
+    fn extend<'c>(
+        path: &mut Vec<&'c Certificate>,
+        pool: &[&'c Certificate],
+        result: &mut Vec<Vec<&'c Certificate>>,
+    ) {
+        let last = path[path.len() - 1];
+        let issuers = pool
+            .iter()
+            .copied()
+            .filter(|candidate| {
+                !path.iter().any(|cert| std::ptr::eq(*cert, *candidate))
+                    && candidate.0.issued(&last.0) == X509VerifyResult::OK
+            })
+            .collect::<Vec<_>>();
+        let mut found = false;
+        for issuer in issuers {
+            if result.len() >= MAX_CANDIDATE_PATHS {
+                return;
+            }
+            found = true;
+            path.push(issuer);
+            extend(path, pool, result);
+            path.pop();
+        }
+        if !found && path.len() > 1 {
+            result.push(path[1..].to_vec());
+        }
+    }
+
+    let mut result = Vec::new();
+    extend(&mut vec![cert], pool, &mut result);
+    result
+}
+
+/// Single failure found during the chain verification
+#[derive(Debug)]
+pub struct VerifyFailure {
+    depth: usize,
+    certificate: Option<Certificate>,
+    error: PkiError,
+}
+
+impl VerifyFailure {
+    fn new(depth: usize, cert: Option<&X509>, error: PkiError) -> Self {
+        Self {
+            depth,
+            certificate: cert.cloned().map(Certificate),
+            error,
+        }
+    }
+
+    /// Position of the offending certificate in the verified path, 0 is the leaf certificate
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Offending certificate, if known
+    pub fn certificate(&self) -> Option<&Certificate> {
+        self.certificate.as_ref()
+    }
+
+    /// Subject name of the offending certificate, if known
+    pub fn subject(&self) -> Option<CertNameRef<'_>> {
+        self.certificate.as_ref().map(|cert| cert.subject_name())
+    }
+
+    /// Verification error
+    pub fn error(&self) -> &PkiError {
+        &self.error
+    }
+}
+
+/// Node of the valid policy tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyNode {
+    policy: String,
+    parent: Option<String>,
+}
+
+impl PolicyNode {
+    /// Valid policy OID of the node
+    pub fn policy(&self) -> &str {
+        &self.policy
+    }
+
+    /// Valid policy OID of the parent node in the previous level
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+}
+
+/// Valid policy tree built during the policy checking
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyTree {
+    levels: Vec<Vec<PolicyNode>>,
+    authority_policies: Vec<String>,
+    user_policies: Vec<String>,
+}
+
+impl PolicyTree {
+    /// Tree levels, starting with the trust anchor level
+    pub fn levels(&self) -> &[Vec<PolicyNode>] {
+        &self.levels
+    }
+
+    /// Policies valid for the whole path regardless of the acceptable policy set
+    pub fn authority_policies(&self) -> &[String] {
+        &self.authority_policies
+    }
+
+    /// Policies valid for the whole path which are also in the acceptable policy set
+    pub fn user_policies(&self) -> &[String] {
+        &self.user_policies
+    }
+}
+
+/// Extract the valid policy tree from the verified context
+fn policy_tree(context: &X509StoreContextRef) -> Option<PolicyTree> {
+    // SAFETY: the tree is owned by the context and all pointers are checked for null
+    unsafe {
+        let tree = ffi::X509_STORE_CTX_get0_policy_tree(context.as_ptr());
+        if tree.is_null() {
+            return None;
+        }
+        let node_policy = |node: *const ffi::X509_POLICY_NODE| {
+            let policy = ffi::X509_policy_node_get0_policy(node);
+            (!policy.is_null()).then(|| oid_string(Asn1ObjectRef::from_ptr(policy as *mut _)))
+        };
+        let stack_policies = |stack: *mut openssl_sys::OPENSSL_STACK| {
+            if stack.is_null() {
+                return Vec::new();
+            }
+            (0..openssl_sys::OPENSSL_sk_num(stack))
+                .filter_map(|i| node_policy(openssl_sys::OPENSSL_sk_value(stack, i) as *const _))
+                .collect()
+        };
+
+        let levels = (0..ffi::X509_policy_tree_level_count(tree))
+            .map(|i| {
+                let level = ffi::X509_policy_tree_get0_level(tree, i);
+                (0..ffi::X509_policy_level_node_count(level))
+                    .filter_map(|j| {
+                        let node = ffi::X509_policy_level_get0_node(level, j);
+                        Some(PolicyNode {
+                            policy: node_policy(node)?,
+                            parent: node_policy(ffi::X509_policy_node_get0_parent(node)),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(PolicyTree {
+            levels,
+            authority_policies: stack_policies(ffi::X509_policy_tree_get0_policies(tree)),
+            user_policies: stack_policies(ffi::X509_policy_tree_get0_user_policies(tree)),
+        })
+    }
+}
+
+/// Detailed result of the chain verification
+#[derive(Debug)]
+pub struct VerifyReport {
+    failures: Vec<VerifyFailure>,
+    path: Vec<Certificate>,
+    policy_tree: Option<PolicyTree>,
+}
+
+impl VerifyReport {
+    /// Return true if the chain has been verified without failures
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// All failures in the order they were found
+    pub fn failures(&self) -> &[VerifyFailure] {
+        &self.failures
+    }
+
+    /// Certificate path built by the verifier, starting with the leaf certificate.
+    /// For a valid chain the last element is the trust anchor.
+    pub fn path(&self) -> &[Certificate] {
+        &self.path
+    }
+
+    /// Valid policy tree if the policy checking has been enabled in the verifier
+    pub fn policy_tree(&self) -> Option<&PolicyTree> {
+        self.policy_tree.as_ref()
+    }
+
+    /// Convert the report into the validated path or the first failure
+    pub fn into_result(self) -> Result<Vec<Certificate>> {
+        match self.failures.into_iter().next() {
+            Some(failure) => Err(failure.error),
+            None => Ok(self.path),
+        }
+    }
+}
+
+type CollectedError = (X509VerifyResult, usize, Option<X509>);
+
+thread_local! {
+    static VERIFY_ERRORS: RefCell<Vec<CollectedError>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Verification callback which records every error and lets OpenSSL continue
+unsafe extern "C" fn collect_verify_error(ok: c_int, ctx: *mut X509_STORE_CTX) -> c_int {
+    if ok == 0 {
+        let context = X509StoreContextRef::from_ptr(ctx);
+        VERIFY_ERRORS.with(|errors| {
+            errors.borrow_mut().push((
+                context.error(),
+                context.error_depth() as usize,
+                context.current_cert().map(|cert| cert.to_owned()),
+            ))
+        });
+    }
+    1
+}
+
+/// Convert OpenSSL verification error into the PKI error
+fn map_verify_error(error: X509VerifyResult, identity: Option<PeerIdentity>) -> PkiError {
+    match (error.as_raw(), identity) {
+        (X509_V_ERR_HOSTNAME_MISMATCH, Some(PeerIdentity::Host(host))) => {
+            PkiError::IdentityMismatch(host.to_owned())
+        }
+        (X509_V_ERR_IP_ADDRESS_MISMATCH, Some(PeerIdentity::Ip(ip))) => {
+            PkiError::IdentityMismatch(ip.to_string())
+        }
+        (X509_V_ERR_EMAIL_MISMATCH, Some(PeerIdentity::Email(email))) => {
+            PkiError::IdentityMismatch(email.to_owned())
         }
+        _ => PkiError::Verify(error),
     }
 }
@@ -0,0 +1,183 @@
+//! Minimal DER encoding and decoding helpers
+use crate::model::{PkiError, Result};
+
+pub(crate) const TAG_BOOLEAN: u8 = 0x01;
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_NULL: u8 = 0x05;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_UTF8_STRING: u8 = 0x0c;
+pub(crate) const TAG_IA5_STRING: u8 = 0x16;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+
+/// Encode a tag-length-value triple
+pub(crate) fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(content.len() + 6);
+    result.push(tag);
+    let len = content.len();
+    if len < 0x80 {
+        result.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        result.push(0x80 | (bytes.len() - skip) as u8);
+        result.extend_from_slice(&bytes[skip..]);
+    }
+    result.extend_from_slice(content);
+    result
+}
+
+/// Decode a single tag-length-value triple, returning the tag, content and the remaining data
+pub(crate) fn read_tlv(data: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first().ok_or(PkiError::InvalidParameters)?;
+    let (&first, rest) = rest.split_first().ok_or(PkiError::InvalidParameters)?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() || rest.len() < count {
+            return Err(PkiError::InvalidParameters);
+        }
+        let len = rest[..count]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, &rest[count..])
+    };
+    if rest.len() < len {
+        return Err(PkiError::InvalidParameters);
+    }
+    Ok((tag, &rest[..len], &rest[len..]))
+}
+
+/// Sequential reader of DER-encoded values
+pub(crate) struct DerReader<'a>(&'a [u8]);
+
+impl<'a> DerReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self(data)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Read next value returning its tag and content
+    pub(crate) fn read(&mut self) -> Result<(u8, &'a [u8])> {
+        let (tag, content, rest) = read_tlv(self.0)?;
+        self.0 = rest;
+        Ok((tag, content))
+    }
+
+    /// Read next value which must have a given tag
+    pub(crate) fn read_tag(&mut self, expected: u8) -> Result<&'a [u8]> {
+        match self.read()? {
+            (tag, content) if tag == expected => Ok(content),
+            _ => Err(PkiError::InvalidParameters),
+        }
+    }
+}
+
+/// Encode dotted OID string into the OBJECT IDENTIFIER content octets
+pub(crate) fn encode_oid(oid: &str) -> Result<Vec<u8>> {
+    let arcs = oid
+        .split('.')
+        .map(|arc| arc.parse::<u64>().map_err(|_| PkiError::InvalidParameters))
+        .collect::<Result<Vec<_>>>()?;
+    if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+        return Err(PkiError::InvalidParameters);
+    }
+    let first = arcs[0]
+        .checked_mul(40)
+        .and_then(|v| v.checked_add(arcs[1]))
+        .ok_or(PkiError::InvalidParameters)?;
+    let mut result = Vec::new();
+    for arc in std::iter::once(first).chain(arcs[2..].iter().copied()) {
+        let mut bytes = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            bytes.push(0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        result.extend(bytes.iter().rev());
+    }
+    Ok(result)
+}
+
+/// Encode OBJECT IDENTIFIER value from the dotted OID string
+pub(crate) fn oid(oid: &str) -> Result<Vec<u8>> {
+    Ok(tlv(TAG_OID, &encode_oid(oid)?))
+}
+
+/// Encode unsigned INTEGER content octets
+pub(crate) fn encode_uint(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count().min(7);
+    let mut result = Vec::with_capacity(9);
+    if bytes[skip] & 0x80 != 0 {
+        result.push(0);
+    }
+    result.extend_from_slice(&bytes[skip..]);
+    result
+}
+
+/// Encode named bit string content octets from the bit positions, with trailing zero bits removed
+pub(crate) fn encode_named_bits<I>(bits: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut bytes: Vec<u8> = Vec::new();
+    for bit in bits {
+        if bytes.len() <= bit / 8 {
+            bytes.resize(bit / 8 + 1, 0);
+        }
+        bytes[bit / 8] |= 0x80 >> (bit % 8);
+    }
+    let unused = bytes.last().map(|b| b.trailing_zeros()).unwrap_or(0);
+    let mut result = vec![unused as u8];
+    result.extend(bytes);
+    result
+}
+
+/// Decode named bit string content octets into the set bit positions
+pub(crate) fn decode_named_bits(content: &[u8]) -> Result<Vec<usize>> {
+    let (&unused, bytes) = content.split_first().ok_or(PkiError::InvalidParameters)?;
+    if unused > 7 || (bytes.is_empty() && unused != 0) {
+        return Err(PkiError::InvalidParameters);
+    }
+    Ok((0..bytes.len() * 8)
+        .filter(|bit| bytes[bit / 8] & (0x80 >> (bit % 8)) != 0)
+        .collect())
+}
+
+/// Decode OBJECT IDENTIFIER content octets into a dotted OID string
+pub(crate) fn decode_oid(content: &[u8]) -> Result<String> {
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+    for (i, b) in content.iter().enumerate() {
+        if value > u64::MAX >> 7 {
+            return Err(PkiError::InvalidParameters);
+        }
+        value = (value << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        } else if i == content.len() - 1 {
+            return Err(PkiError::InvalidParameters);
+        }
+    }
+    if arcs.is_empty() {
+        return Err(PkiError::InvalidParameters);
+    }
+    Ok(arcs
+        .iter()
+        .map(|arc| arc.to_string())
+        .collect::<Vec<_>>()
+        .join("."))
+}
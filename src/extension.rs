@@ -0,0 +1,636 @@
+//! X.509 extensions
+use std::{
+    ffi::{c_int, CStr, CString},
+    net::IpAddr,
+    ptr, slice,
+};
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl::{
+    asn1::{Asn1Object, Asn1ObjectRef, Asn1OctetString},
+    conf::{Conf, ConfMethod},
+    error::ErrorStack,
+    x509::{self, X509Builder, X509Extension, X509ExtensionRef, X509Ref, X509v3Context},
+};
+
+use crate::{
+    der, ffi,
+    model::{CertName, PkiError, Result},
+    name::oid_string,
+};
+
+/// Subject key identifier extension
+pub const OID_SUBJECT_KEY_IDENTIFIER: &str = "2.5.29.14";
+/// Key usage extension
+pub const OID_KEY_USAGE: &str = "2.5.29.15";
+/// Subject alternative name extension
+pub const OID_SUBJECT_ALT_NAME: &str = "2.5.29.17";
+/// Basic constraints extension
+pub const OID_BASIC_CONSTRAINTS: &str = "2.5.29.19";
+/// Name constraints extension
+pub const OID_NAME_CONSTRAINTS: &str = "2.5.29.30";
+/// CRL distribution points extension
+pub const OID_CRL_DISTRIBUTION_POINTS: &str = "2.5.29.31";
+/// Certificate policies extension
+pub const OID_CERTIFICATE_POLICIES: &str = "2.5.29.32";
+/// Special policy identifier which matches any certificate policy
+pub const OID_ANY_POLICY: &str = "2.5.29.32.0";
+/// Policy mappings extension
+pub const OID_POLICY_MAPPINGS: &str = "2.5.29.33";
+/// Authority key identifier extension
+pub const OID_AUTHORITY_KEY_IDENTIFIER: &str = "2.5.29.35";
+/// Policy constraints extension
+pub const OID_POLICY_CONSTRAINTS: &str = "2.5.29.36";
+/// Extended key usage extension
+pub const OID_EXTENDED_KEY_USAGE: &str = "2.5.29.37";
+/// Extended key usage purpose which allows any purpose
+pub const OID_ANY_EXTENDED_KEY_USAGE: &str = "2.5.29.37.0";
+/// Freshest CRL (delta CRL distribution points) extension
+pub const OID_FRESHEST_CRL: &str = "2.5.29.46";
+/// Inhibit any-policy extension
+pub const OID_INHIBIT_ANY_POLICY: &str = "2.5.29.54";
+/// Authority information access extension
+pub const OID_AUTHORITY_INFO_ACCESS: &str = "1.3.6.1.5.5.7.1.1";
+/// TLS server authentication extended key usage
+pub const OID_SERVER_AUTH: &str = "1.3.6.1.5.5.7.3.1";
+/// TLS client authentication extended key usage
+pub const OID_CLIENT_AUTH: &str = "1.3.6.1.5.5.7.3.2";
+/// Code signing extended key usage
+pub const OID_CODE_SIGNING: &str = "1.3.6.1.5.5.7.3.3";
+/// Email protection extended key usage
+pub const OID_EMAIL_PROTECTION: &str = "1.3.6.1.5.5.7.3.4";
+/// Time stamping extended key usage
+pub const OID_TIME_STAMPING: &str = "1.3.6.1.5.5.7.3.8";
+/// OCSP response signing extended key usage
+pub const OID_OCSP_SIGNING: &str = "1.3.6.1.5.5.7.3.9";
+/// IPsec IKE extended key usage, RFC 4945
+pub const OID_IPSEC_IKE: &str = "1.3.6.1.5.5.7.3.17";
+const OID_AD_OCSP: &str = "1.3.6.1.5.5.7.48.1";
+const OID_AD_CA_ISSUERS: &str = "1.3.6.1.5.5.7.48.2";
+const OID_QT_CPS: &str = "1.3.6.1.5.5.7.2.1";
+const OID_QT_UNOTICE: &str = "1.3.6.1.5.5.7.2.2";
+
+/// X.509 certificate extension with a DER-encoded value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extension {
+    oid: String,
+    critical: bool,
+    value: Vec<u8>,
+}
+
+impl Extension {
+    /// Create extension with a given OID, criticality and DER-encoded value
+    pub fn new<S, V>(oid: S, critical: bool, value: V) -> Self
+    where
+        S: AsRef<str>,
+        V: Into<Vec<u8>>,
+    {
+        Self {
+            oid: oid.as_ref().to_owned(),
+            critical,
+            value: value.into(),
+        }
+    }
+
+    /// Extension OID
+    pub fn oid(&self) -> &str {
+        &self.oid
+    }
+
+    /// Extension criticality
+    pub fn critical(&self) -> bool {
+        self.critical
+    }
+
+    /// DER-encoded extension value
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Convert the extension into the OpenSSL representation
+    pub(crate) fn to_openssl(&self) -> Result<X509Extension> {
+        der_extension(&self.oid, self.critical, &self.value)
+    }
+
+    /// Convert OpenSSL extension into this representation
+    pub(crate) fn from_openssl(ext: &X509ExtensionRef) -> Self {
+        // SAFETY: the extension pointer is valid
+        unsafe { Self::from_ptr(ext.as_ptr()) }
+    }
+
+    /// # Safety
+    /// The pointer must be a valid extension
+    unsafe fn from_ptr(ext: *mut openssl_sys::X509_EXTENSION) -> Self {
+        let object = Asn1ObjectRef::from_ptr(openssl_sys::X509_EXTENSION_get_object(ext));
+        let data = openssl_sys::X509_EXTENSION_get_data(ext) as *const openssl_sys::ASN1_STRING;
+        let value = slice::from_raw_parts(
+            openssl_sys::ASN1_STRING_get0_data(data),
+            openssl_sys::ASN1_STRING_length(data) as usize,
+        );
+        Self {
+            oid: oid_string(object),
+            critical: openssl_sys::X509_EXTENSION_get_critical(ext) != 0,
+            value: value.to_vec(),
+        }
+    }
+}
+
+/// Create extensions from a section of the OpenSSL x509v3 configuration
+pub(crate) fn config_extensions(
+    builder: &X509Builder,
+    issuer: Option<&X509Ref>,
+    config: &str,
+    section: Option<&str>,
+) -> Result<Vec<Extension>> {
+    let conf = Conf::new(ConfMethod::default())?;
+    let len = c_int::try_from(config.len()).map_err(|_| PkiError::InvalidParameters)?;
+    let section =
+        CString::new(section.unwrap_or("default")).map_err(|_| PkiError::InvalidParameters)?;
+    let context = builder.x509v3_context(issuer, Some(&conf));
+    let mut result = Vec::new();
+
+    // SAFETY: all pointers are checked for null, the configuration outlives the section values
+    unsafe {
+        let bio = openssl_sys::BIO_new_mem_buf(config.as_ptr() as *const _, len);
+        if bio.is_null() {
+            return Err(ErrorStack::get().into());
+        }
+        let loaded = ffi::NCONF_load_bio(conf.as_ptr(), bio, ptr::null_mut());
+        openssl_sys::BIO_free_all(bio);
+        if loaded <= 0 {
+            return Err(ErrorStack::get().into());
+        }
+
+        let values = ffi::NCONF_get_section(conf.as_ptr(), section.as_ptr());
+        if values.is_null() {
+            return Err(PkiError::InvalidParameters);
+        }
+        for i in 0..openssl_sys::OPENSSL_sk_num(values) {
+            let value = &*(openssl_sys::OPENSSL_sk_value(values, i) as *const ffi::CONF_VALUE);
+            let ext = openssl_sys::X509V3_EXT_nconf(
+                conf.as_ptr(),
+                context.as_ptr(),
+                value.name,
+                value.value,
+            );
+            if ext.is_null() {
+                let name = CStr::from_ptr(value.name).to_string_lossy();
+                return Err(PkiError::InvalidExtension(name.into_owned()));
+            }
+            result.push(Extension::from_openssl(&X509Extension::from_ptr(ext)));
+        }
+    }
+    Ok(result)
+}
+
+/// Return all extensions of the certificate in their original order
+pub(crate) fn cert_extensions(cert: &X509Ref) -> Vec<Extension> {
+    // SAFETY: the extension pointers are owned by the certificate and valid for its lifetime
+    unsafe {
+        let count = openssl_sys::X509_get_ext_count(cert.as_ptr());
+        (0..count)
+            .map(|i| Extension::from_ptr(openssl_sys::X509_get_ext(cert.as_ptr(), i)))
+            .collect()
+    }
+}
+
+/// Return the first extension with a given OID
+pub(crate) fn cert_extension(cert: &X509Ref, oid: &str) -> Option<Extension> {
+    cert_extensions(cert).into_iter().find(|ext| ext.oid == oid)
+}
+
+/// Return extended key usage OIDs of the certificate, `None` if the extension is absent
+pub(crate) fn extended_key_usage(cert: &X509Ref) -> Result<Option<Vec<String>>> {
+    let ext = match cert_extension(cert, OID_EXTENDED_KEY_USAGE) {
+        Some(ext) => ext,
+        None => return Ok(None),
+    };
+    let mut reader =
+        der::DerReader::new(der::DerReader::new(&ext.value).read_tag(der::TAG_SEQUENCE)?);
+    let mut oids = Vec::new();
+    while !reader.is_empty() {
+        oids.push(der::decode_oid(reader.read_tag(der::TAG_OID)?)?);
+    }
+    Ok(Some(oids))
+}
+
+/// Create the subject alternative name extension, the type of each name is detected from its format
+pub(crate) fn subject_alt_name(names: &[String], ctx: &X509v3Context) -> Result<Extension> {
+    let mut subj_alt_name = x509::extension::SubjectAlternativeName::new();
+    for name in names {
+        if name.parse::<IpAddr>().is_ok() {
+            subj_alt_name.ip(name);
+        } else if name.contains('@') {
+            subj_alt_name.email(name);
+        } else {
+            subj_alt_name.dns(name);
+        }
+    }
+    let ext = subj_alt_name.build(ctx)?;
+    Ok(Extension::from_openssl(&ext))
+}
+
+/// Encode basic constraints extension value
+pub(crate) fn basic_constraints(ca: bool, path_len: Option<u32>) -> Vec<u8> {
+    let mut content = Vec::new();
+    if ca {
+        content.extend(der::tlv(der::TAG_BOOLEAN, &[0xff]));
+    }
+    if let Some(path_len) = path_len {
+        content.extend(der::tlv(
+            der::TAG_INTEGER,
+            &der::encode_uint(path_len as u64),
+        ));
+    }
+    der::tlv(der::TAG_SEQUENCE, &content)
+}
+
+/// Decode basic constraints extension value into the CA flag and path length
+pub(crate) fn decode_basic_constraints(value: &[u8]) -> Result<(bool, Option<u32>)> {
+    let mut reader = der::DerReader::new(der::DerReader::new(value).read_tag(der::TAG_SEQUENCE)?);
+    let mut ca = false;
+    let mut path_len = None;
+    while !reader.is_empty() {
+        match reader.read()? {
+            (der::TAG_BOOLEAN, [flag]) => ca = *flag != 0,
+            (der::TAG_INTEGER, value) if !value.is_empty() && value.len() <= 5 => {
+                let value = value.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+                path_len = Some(u32::try_from(value).map_err(|_| PkiError::InvalidParameters)?);
+            }
+            _ => return Err(PkiError::InvalidParameters),
+        }
+    }
+    Ok((ca, path_len))
+}
+
+/// Encode extended key usage extension value
+pub(crate) fn extended_key_usage_value(oids: &[String]) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    for oid in oids {
+        content.extend(der::oid(oid)?);
+    }
+    Ok(der::tlv(der::TAG_SEQUENCE, &content))
+}
+
+/// Create OpenSSL extension from the DER-encoded value
+fn der_extension(oid: &str, critical: bool, value: &[u8]) -> Result<X509Extension> {
+    let object = Asn1Object::from_str(oid)?;
+    let value = Asn1OctetString::new_from_bytes(value)?;
+    Ok(X509Extension::new_from_der(&object, critical, &value)?)
+}
+
+/// Name subtree used in the name constraints extension of a CA certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameConstraint(Vec<u8>);
+
+impl NameConstraint {
+    /// DNS subtree, e.g. `example.com` matches the domain itself and all its subdomains
+    pub fn dns(domain: &str) -> Result<Self> {
+        Ok(Self(der::tlv(0x82, ia5_string(domain)?)))
+    }
+
+    /// Email subtree: a full mailbox address, a `host` or a `.domain` for all subdomains
+    pub fn email(email: &str) -> Result<Self> {
+        Ok(Self(der::tlv(0x81, ia5_string(email)?)))
+    }
+
+    /// IP network subtree with a given prefix length, IPv4 or IPv6.
+    /// The address must be the network address: host bits beyond the prefix must be zero.
+    pub fn ip(addr: IpAddr, prefix: u8) -> Result<Self> {
+        let (mut content, bits) = match addr {
+            IpAddr::V4(v4) => (v4.octets().to_vec(), 32),
+            IpAddr::V6(v6) => (v6.octets().to_vec(), 128),
+        };
+        if prefix > bits {
+            return Err(PkiError::InvalidParameters);
+        }
+        let mask: Vec<u8> = (0..bits / 8)
+            .map(|i| {
+                let ones = prefix.saturating_sub(i * 8).min(8);
+                (0xffu16 << (8 - ones)) as u8
+            })
+            .collect();
+        if content
+            .iter()
+            .zip(&mask)
+            .any(|(byte, mask)| byte & !mask != 0)
+        {
+            return Err(PkiError::InvalidParameters);
+        }
+        content.extend(mask);
+        Ok(Self(der::tlv(0x87, &content)))
+    }
+
+    /// IP network subtree in the CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`
+    pub fn ip_network(cidr: &str) -> Result<Self> {
+        let (addr, prefix) = cidr.split_once('/').ok_or(PkiError::InvalidParameters)?;
+        let addr = addr.parse().map_err(|_| PkiError::InvalidParameters)?;
+        let prefix = prefix.parse().map_err(|_| PkiError::InvalidParameters)?;
+        Self::ip(addr, prefix)
+    }
+
+    /// Directory name subtree, matches all names starting with the given RDNs
+    pub fn directory_name(name: &CertName) -> Result<Self> {
+        Ok(Self(der::tlv(0xa4, &name.to_der()?)))
+    }
+}
+
+fn ia5_string(value: &str) -> Result<&[u8]> {
+    if value.is_ascii() {
+        Ok(value.as_bytes())
+    } else {
+        Err(PkiError::InvalidParameters)
+    }
+}
+
+/// Encode the authority key identifier extension value from the subject key identifier value
+pub(crate) fn authority_key_identifier(subject_key_id: &[u8]) -> Result<Vec<u8>> {
+    let key_id = der::DerReader::new(subject_key_id).read_tag(der::TAG_OCTET_STRING)?;
+    Ok(der::tlv(der::TAG_SEQUENCE, &der::tlv(0x80, key_id)))
+}
+
+/// Encode the name constraints extension value
+pub(crate) fn name_constraints(
+    permitted: &[NameConstraint],
+    excluded: &[NameConstraint],
+) -> Vec<u8> {
+    let subtrees = |tag: u8, names: &[NameConstraint]| {
+        if names.is_empty() {
+            Vec::new()
+        } else {
+            let content = names
+                .iter()
+                .flat_map(|name| der::tlv(der::TAG_SEQUENCE, &name.0))
+                .collect::<Vec<_>>();
+            der::tlv(tag, &content)
+        }
+    };
+    let mut content = subtrees(0xa0, permitted);
+    content.extend(subtrees(0xa1, excluded));
+    der::tlv(der::TAG_SEQUENCE, &content)
+}
+
+/// Policy information of the certificate policies extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificatePolicy {
+    oid: String,
+    cps_uri: Option<String>,
+    user_notice: Option<String>,
+}
+
+impl CertificatePolicy {
+    /// Create policy information with a given policy OID
+    pub fn new<S: AsRef<str>>(oid: S) -> Self {
+        Self {
+            oid: oid.as_ref().to_owned(),
+            cps_uri: None,
+            user_notice: None,
+        }
+    }
+
+    /// Create the special anyPolicy policy information
+    pub fn any() -> Self {
+        Self::new(OID_ANY_POLICY)
+    }
+
+    /// Specify URI of the certification practice statement
+    pub fn cps_uri<S: AsRef<str>>(mut self, uri: S) -> Self {
+        self.cps_uri = Some(uri.as_ref().to_owned());
+        self
+    }
+
+    /// Specify explicit text of the user notice
+    pub fn user_notice<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.user_notice = Some(text.as_ref().to_owned());
+        self
+    }
+
+    /// Policy OID
+    pub fn oid(&self) -> &str {
+        &self.oid
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut qualifiers = Vec::new();
+        if let Some(ref uri) = self.cps_uri {
+            let mut qualifier = der::oid(OID_QT_CPS)?;
+            qualifier.extend(der::tlv(der::TAG_IA5_STRING, ia5_string(uri)?));
+            qualifiers.extend(der::tlv(der::TAG_SEQUENCE, &qualifier));
+        }
+        if let Some(ref text) = self.user_notice {
+            let mut qualifier = der::oid(OID_QT_UNOTICE)?;
+            let notice = der::tlv(der::TAG_UTF8_STRING, text.as_bytes());
+            qualifier.extend(der::tlv(der::TAG_SEQUENCE, &notice));
+            qualifiers.extend(der::tlv(der::TAG_SEQUENCE, &qualifier));
+        }
+        let mut content = der::oid(&self.oid)?;
+        if !qualifiers.is_empty() {
+            content.extend(der::tlv(der::TAG_SEQUENCE, &qualifiers));
+        }
+        Ok(der::tlv(der::TAG_SEQUENCE, &content))
+    }
+}
+
+/// Encode the certificate policies extension value
+pub(crate) fn certificate_policies(policies: &[CertificatePolicy]) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    for policy in policies {
+        content.extend(policy.encode()?);
+    }
+    Ok(der::tlv(der::TAG_SEQUENCE, &content))
+}
+
+/// Encode the policy mappings extension value
+pub(crate) fn policy_mappings(mappings: &[(String, String)]) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    for (issuer_policy, subject_policy) in mappings {
+        let mut mapping = der::oid(issuer_policy)?;
+        mapping.extend(der::oid(subject_policy)?);
+        content.extend(der::tlv(der::TAG_SEQUENCE, &mapping));
+    }
+    Ok(der::tlv(der::TAG_SEQUENCE, &content))
+}
+
+/// Encode the policy constraints extension value
+pub(crate) fn policy_constraints(
+    require_explicit_policy: Option<u32>,
+    inhibit_policy_mapping: Option<u32>,
+) -> Vec<u8> {
+    let mut content = Vec::new();
+    if let Some(skip_certs) = require_explicit_policy {
+        content.extend(der::tlv(0x80, &der::encode_uint(skip_certs as _)));
+    }
+    if let Some(skip_certs) = inhibit_policy_mapping {
+        content.extend(der::tlv(0x81, &der::encode_uint(skip_certs as _)));
+    }
+    der::tlv(der::TAG_SEQUENCE, &content)
+}
+
+/// Encode the inhibit anyPolicy extension value
+pub(crate) fn inhibit_any_policy(skip_certs: u32) -> Vec<u8> {
+    der::tlv(der::TAG_INTEGER, &der::encode_uint(skip_certs as _))
+}
+
+/// Revocation reason used in the CRL distribution points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RevocationReason {
+    KeyCompromise,
+    CaCompromise,
+    AffiliationChanged,
+    Superseded,
+    CessationOfOperation,
+    CertificateHold,
+    PrivilegeWithdrawn,
+    AaCompromise,
+}
+
+impl RevocationReason {
+    fn bit(&self) -> usize {
+        match self {
+            Self::KeyCompromise => 1,
+            Self::CaCompromise => 2,
+            Self::AffiliationChanged => 3,
+            Self::Superseded => 4,
+            Self::CessationOfOperation => 5,
+            Self::CertificateHold => 6,
+            Self::PrivilegeWithdrawn => 7,
+            Self::AaCompromise => 8,
+        }
+    }
+}
+
+/// CRL distribution point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistributionPoint {
+    uris: Vec<String>,
+    reasons: Vec<RevocationReason>,
+    crl_issuer: Option<Vec<u8>>,
+}
+
+impl DistributionPoint {
+    /// Create a distribution point with a given CRL URI
+    pub fn new<S: AsRef<str>>(uri: S) -> Self {
+        Self {
+            uris: vec![uri.as_ref().to_owned()],
+            reasons: Vec::new(),
+            crl_issuer: None,
+        }
+    }
+
+    /// Add an alternative URI of the same CRL
+    pub fn uri<S: AsRef<str>>(mut self, uri: S) -> Self {
+        self.uris.push(uri.as_ref().to_owned());
+        self
+    }
+
+    /// Restrict the CRL to the given revocation reasons
+    pub fn reasons<I>(mut self, reasons: I) -> Self
+    where
+        I: IntoIterator<Item = RevocationReason>,
+    {
+        self.reasons = reasons.into_iter().collect();
+        self
+    }
+
+    /// Specify the CRL issuer if it is different from the certificate issuer
+    pub fn crl_issuer(mut self, issuer: &CertName) -> Result<Self> {
+        self.crl_issuer = Some(issuer.to_der()?);
+        Ok(self)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut names = Vec::new();
+        for uri in &self.uris {
+            names.extend(der::tlv(0x86, ia5_string(uri)?));
+        }
+        let mut content = der::tlv(0xa0, &der::tlv(0xa0, &names));
+        if !self.reasons.is_empty() {
+            content.extend(der::tlv(
+                0x81,
+                &der::encode_named_bits(self.reasons.iter().map(|r| r.bit())),
+            ));
+        }
+        if let Some(ref issuer) = self.crl_issuer {
+            content.extend(der::tlv(0xa2, &der::tlv(0xa4, issuer)));
+        }
+        Ok(der::tlv(der::TAG_SEQUENCE, &content))
+    }
+}
+
+/// Revocation and issuer information locations: CRL distribution points, freshest CRL,
+/// OCSP responders and CA issuer certificates
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevocationInfo {
+    crl_distribution_points: Vec<DistributionPoint>,
+    freshest_crl: Vec<DistributionPoint>,
+    ocsp_responders: Vec<String>,
+    ca_issuers: Vec<String>,
+}
+
+impl RevocationInfo {
+    /// Create empty revocation information
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a CRL distribution point
+    pub fn crl_distribution_point(mut self, point: DistributionPoint) -> Self {
+        self.crl_distribution_points.push(point);
+        self
+    }
+
+    /// Add a delta CRL distribution point for the freshest CRL extension
+    pub fn freshest_crl(mut self, point: DistributionPoint) -> Self {
+        self.freshest_crl.push(point);
+        self
+    }
+
+    /// Add an OCSP responder URI to the authority information access extension
+    pub fn ocsp_responder<S: AsRef<str>>(mut self, uri: S) -> Self {
+        self.ocsp_responders.push(uri.as_ref().to_owned());
+        self
+    }
+
+    /// Add an issuer certificate URI to the authority information access extension
+    pub fn ca_issuers<S: AsRef<str>>(mut self, uri: S) -> Self {
+        self.ca_issuers.push(uri.as_ref().to_owned());
+        self
+    }
+
+    /// Create the extensions for the revocation information
+    pub(crate) fn extensions(&self) -> Result<Vec<Extension>> {
+        let mut result = Vec::new();
+        if !self.crl_distribution_points.is_empty() {
+            let value = distribution_points(&self.crl_distribution_points)?;
+            result.push(Extension::new(OID_CRL_DISTRIBUTION_POINTS, false, value));
+        }
+        if !self.freshest_crl.is_empty() {
+            let value = distribution_points(&self.freshest_crl)?;
+            result.push(Extension::new(OID_FRESHEST_CRL, false, value));
+        }
+        if !self.ocsp_responders.is_empty() || !self.ca_issuers.is_empty() {
+            let mut content = Vec::new();
+            let locations = self
+                .ocsp_responders
+                .iter()
+                .map(|uri| (OID_AD_OCSP, uri))
+                .chain(self.ca_issuers.iter().map(|uri| (OID_AD_CA_ISSUERS, uri)));
+            for (method, uri) in locations {
+                let mut description = der::oid(method)?;
+                description.extend(der::tlv(0x86, ia5_string(uri)?));
+                content.extend(der::tlv(der::TAG_SEQUENCE, &description));
+            }
+            let value = der::tlv(der::TAG_SEQUENCE, &content);
+            result.push(Extension::new(OID_AUTHORITY_INFO_ACCESS, false, value));
+        }
+        Ok(result)
+    }
+}
+
+fn distribution_points(points: &[DistributionPoint]) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    for point in points {
+        content.extend(point.encode()?);
+    }
+    Ok(der::tlv(der::TAG_SEQUENCE, &content))
+}
@@ -0,0 +1,104 @@
+//! Bindings to the OpenSSL functions which are not exposed by `openssl-sys`
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::ffi::{c_char, c_int, c_long, c_uchar, c_ulong, c_void};
+
+use openssl_sys::{
+    stack_st_ASN1_OBJECT, ASN1_OBJECT, BIO, CONF, OPENSSL_STACK, X509, X509_ALGOR, X509_NAME,
+    X509_NAME_ENTRY, X509_REQ, X509_STORE_CTX, X509_VERIFY_PARAM,
+};
+
+#[repr(C)]
+pub struct CONF_VALUE {
+    pub section: *mut c_char,
+    pub name: *mut c_char,
+    pub value: *mut c_char,
+}
+
+pub const V_ASN1_UNDEF: c_int = -1;
+
+pub enum X509_POLICY_TREE {}
+pub enum X509_POLICY_LEVEL {}
+pub enum X509_POLICY_NODE {}
+
+pub type X509_STORE_CTX_verify_cb =
+    Option<unsafe extern "C" fn(ok: c_int, ctx: *mut X509_STORE_CTX) -> c_int>;
+
+extern "C" {
+    pub fn X509_NAME_add_entry_by_OBJ(
+        name: *mut X509_NAME,
+        obj: *const ASN1_OBJECT,
+        type_: c_int,
+        bytes: *const c_uchar,
+        len: c_int,
+        loc: c_int,
+        set: c_int,
+    ) -> c_int;
+
+    pub fn X509_NAME_ENTRY_set(ne: *const X509_NAME_ENTRY) -> c_int;
+
+    pub fn X509_check_purpose(x: *mut X509, id: c_int, ca: c_int) -> c_int;
+
+    #[cfg(openssl_3_0)]
+    pub fn X509_NAME_hash_ex(
+        name: *const X509_NAME,
+        libctx: *mut std::ffi::c_void,
+        propq: *const c_char,
+        ok: *mut c_int,
+    ) -> c_ulong;
+
+    #[cfg(not(openssl_3_0))]
+    pub fn X509_NAME_hash(name: *mut X509_NAME) -> c_ulong;
+
+    pub fn X509_STORE_CTX_set_verify_cb(
+        ctx: *mut X509_STORE_CTX,
+        verify_cb: X509_STORE_CTX_verify_cb,
+    );
+
+    pub fn X509_VERIFY_PARAM_set1_policies(
+        param: *mut X509_VERIFY_PARAM,
+        policies: *mut stack_st_ASN1_OBJECT,
+    ) -> c_int;
+
+    pub fn X509_STORE_CTX_get0_policy_tree(ctx: *const X509_STORE_CTX) -> *mut X509_POLICY_TREE;
+
+    pub fn X509_policy_tree_level_count(tree: *const X509_POLICY_TREE) -> c_int;
+
+    pub fn X509_policy_tree_get0_level(
+        tree: *const X509_POLICY_TREE,
+        i: c_int,
+    ) -> *mut X509_POLICY_LEVEL;
+
+    pub fn X509_policy_tree_get0_policies(tree: *const X509_POLICY_TREE) -> *mut OPENSSL_STACK;
+
+    pub fn X509_policy_tree_get0_user_policies(tree: *const X509_POLICY_TREE)
+        -> *mut OPENSSL_STACK;
+
+    pub fn X509_policy_level_node_count(level: *mut X509_POLICY_LEVEL) -> c_int;
+
+    pub fn X509_policy_level_get0_node(
+        level: *const X509_POLICY_LEVEL,
+        i: c_int,
+    ) -> *mut X509_POLICY_NODE;
+
+    pub fn X509_policy_node_get0_policy(node: *const X509_POLICY_NODE) -> *const ASN1_OBJECT;
+
+    pub fn X509_policy_node_get0_parent(node: *const X509_POLICY_NODE) -> *const X509_POLICY_NODE;
+
+    pub fn NCONF_load_bio(conf: *mut CONF, bp: *mut BIO, eline: *mut c_long) -> c_int;
+
+    pub fn NCONF_get_section(conf: *const CONF, section: *const c_char) -> *mut OPENSSL_STACK;
+
+    pub fn X509_get0_tbs_sigalg(x: *const X509) -> *const X509_ALGOR;
+
+    pub fn X509_ALGOR_set0(
+        alg: *mut X509_ALGOR,
+        aobj: *mut ASN1_OBJECT,
+        ptype: c_int,
+        pval: *mut c_void,
+    ) -> c_int;
+
+    pub fn i2d_re_X509_tbs(x: *mut X509, pp: *mut *mut c_uchar) -> c_int;
+
+    pub fn i2d_re_X509_REQ_tbs(req: *mut X509_REQ, pp: *mut *mut c_uchar) -> c_int;
+}
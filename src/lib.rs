@@ -1,8 +1,30 @@
 #![doc = include_str!("../README.md")]
 
 pub mod chain;
+mod der;
+pub mod extension;
+mod ffi;
 pub mod model;
+pub mod name;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+pub mod request;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod serial;
+pub mod signer;
+pub mod usage;
 pub mod util;
+pub mod validity;
 
 pub use chain::*;
+pub use extension::*;
 pub use model::*;
+pub use name::*;
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::*;
+pub use request::*;
+pub use serial::*;
+pub use signer::*;
+pub use usage::*;
+pub use validity::*;
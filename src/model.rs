@@ -6,13 +6,19 @@ use openssl::{
     error::ErrorStack,
     nid::Nid,
     pkcs12::Pkcs12,
-    pkey::{Id, PKey, Private},
+    pkey::{Id, PKey, Private, Public},
     rsa::Rsa,
     stack::Stack,
     symm::Cipher,
     x509::{X509Name, X509NameEntries, X509NameRef, X509VerifyResult, X509},
 };
 
+use crate::extension::{
+    self, Extension, RevocationInfo, OID_CLIENT_AUTH, OID_CODE_SIGNING, OID_KEY_USAGE,
+    OID_SERVER_AUTH,
+};
+use crate::{name, serial::SerialNumber, usage::KeyUsage, validity::CertTime};
+
 /// PKI result
 pub type Result<T> = std::result::Result<T, PkiError>;
 
@@ -23,6 +29,8 @@ pub enum PkiError {
     #[error(transparent)]
     Openssl(#[from] ErrorStack),
     #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
     SystemTime(#[from] SystemTimeError),
     #[error(transparent)]
     Verify(#[from] X509VerifyResult),
@@ -30,6 +38,47 @@ pub enum PkiError {
     InvalidParameters,
     #[error("No private key in the store")]
     MissingPrivateKey,
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+    #[error("Certificate is not valid for {0}")]
+    IdentityMismatch(String),
+    #[error("Invalid extension: {0}")]
+    InvalidExtension(String),
+    #[error("Certificate at depth {depth} ({subject}) is not valid for {purpose}")]
+    PurposeMismatch {
+        depth: usize,
+        subject: String,
+        purpose: String,
+    },
+    #[error("Invalid key store: certificate at depth {depth} ({subject}): {reason}")]
+    InvalidKeyStore {
+        depth: usize,
+        subject: String,
+        reason: KeyStoreMismatch,
+    },
+    #[cfg(feature = "pkcs11")]
+    #[error(transparent)]
+    Pkcs11(#[from] cryptoki::error::Error),
+    #[cfg(feature = "pkcs11")]
+    #[error("Private key stored in the token cannot be exported")]
+    NonExportableKey,
+    #[cfg(feature = "pkcs11")]
+    #[error("Object with label {0} already exists in the token")]
+    DuplicateLabel(String),
+}
+
+/// Inconsistency found by [`KeyStore::validate`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum KeyStoreMismatch {
+    #[error("private key does not match the certificate")]
+    PrivateKey,
+    #[error("issuer name does not match the subject name of the issuer certificate")]
+    IssuerName,
+    #[error("authority key identifier does not match the subject key identifier of the issuer certificate")]
+    KeyIdentifier,
+    #[error("signature is not valid for the public key of the issuer certificate")]
+    Signature,
 }
 
 /// Private key type
@@ -43,6 +92,7 @@ pub enum PrivateKeyType {
 
 /// Certificate target usage
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CertUsage {
     CA,
     TlsServer,
@@ -63,6 +113,17 @@ impl CertUsage {
         }
     }
 
+    /// Get the extended usage OIDs
+    pub fn extended_usage_oids(&self) -> &'static [&'static str] {
+        match self {
+            Self::CA => &[],
+            Self::TlsServer => &[OID_SERVER_AUTH],
+            Self::TlsClient => &[OID_CLIENT_AUTH],
+            Self::CodeSign => &[OID_CODE_SIGNING],
+            Self::TlsServerAndClient => &[OID_SERVER_AUTH, OID_CLIENT_AUTH],
+        }
+    }
+
     /// Get the usage string
     pub fn usage(&self) -> &'static str {
         match self {
@@ -75,14 +136,22 @@ impl CertUsage {
     }
 }
 
-/// PrivateKey represents a private key
+/// PrivateKey represents a private key held in memory or,
+/// with the `pkcs11` feature, a non-exportable key stored in a PKCS#11 token
+#[derive(Debug, Clone)]
+pub struct PrivateKey(pub(crate) KeyMaterial);
+
 #[derive(Debug, Clone)]
-pub struct PrivateKey(pub(crate) PKey<Private>);
+pub(crate) enum KeyMaterial {
+    Memory(PKey<Private>),
+    #[cfg(feature = "pkcs11")]
+    Token(std::sync::Arc<crate::pkcs11::TokenKey>),
+}
 
 impl PrivateKey {
     /// Create RSA private key with a given bit length
     pub fn new_rsa(bits: u32) -> Result<Self> {
-        Ok(Self(PKey::from_rsa(Rsa::generate(bits)?)?))
+        Ok(PKey::from_rsa(Rsa::generate(bits)?)?.into())
     }
 
     /// Create EC secp256r1 or secp384r1 private key
@@ -92,73 +161,191 @@ impl PrivateKey {
             384 => Nid::SECP384R1,
             _ => return Err(PkiError::InvalidParameters),
         };
-        Ok(Self(PKey::from_ec_key(EcKey::generate(
-            EcGroup::from_curve_name(nid)?.as_ref(),
-        )?)?))
+        Ok(PKey::from_ec_key(EcKey::generate(EcGroup::from_curve_name(nid)?.as_ref())?)?.into())
     }
 
     /// Parse private key from DER format
     pub fn from_der(data: &[u8]) -> Result<Self> {
-        Ok(Self(PKey::private_key_from_der(data)?))
+        Ok(PKey::private_key_from_der(data)?.into())
     }
 
     /// Convert private key to DER format
     pub fn to_der(&self) -> Result<Vec<u8>> {
-        Ok(self.0.private_key_to_der()?)
+        Ok(self.pkey()?.private_key_to_der()?)
     }
 
     /// Parse private key from encrypted PKCS8 DER format
     pub fn from_pkcs8_der(data: &[u8], password: &str) -> Result<Self> {
-        Ok(Self(PKey::private_key_from_pkcs8_passphrase(
-            data,
-            password.as_bytes(),
-        )?))
+        Ok(PKey::private_key_from_pkcs8_passphrase(data, password.as_bytes())?.into())
     }
 
     /// Convert private key to encrypted PKCS8 DER format
     pub fn to_pkcs8_der(&self, password: &str) -> Result<Vec<u8>> {
         Ok(self
-            .0
+            .pkey()?
             .private_key_to_pkcs8_passphrase(Cipher::aes_256_cbc(), password.as_bytes())?)
     }
 
     /// Parse private key from PKCS8 PEM format
     pub fn from_pkcs8_pem(data: &[u8]) -> Result<Self> {
-        Ok(Self(PKey::private_key_from_pem(data)?))
+        Ok(PKey::private_key_from_pem(data)?.into())
     }
 
     /// Convert private key to PKCS8 PEM format
     pub fn to_pkcs8_pem(&self) -> Result<Vec<u8>> {
-        Ok(self.0.private_key_to_pem_pkcs8()?)
+        Ok(self.pkey()?.private_key_to_pem_pkcs8()?)
+    }
+
+    /// Encrypt private key with a password, see [`EncryptedPrivateKey`]
+    pub fn encrypt(&self, password: &str) -> Result<EncryptedPrivateKey> {
+        Ok(EncryptedPrivateKey(self.to_pkcs8_der(password)?))
     }
 
     /// Return number of bits in the private key
     pub fn bits(&self) -> u32 {
-        self.0.bits()
+        match self.0 {
+            KeyMaterial::Memory(ref key) => key.bits(),
+            #[cfg(feature = "pkcs11")]
+            KeyMaterial::Token(ref key) => key.public_key().0.bits(),
+        }
     }
 
     /// Return key type
     pub fn key_type(&self) -> PrivateKeyType {
-        match self.0.id() {
+        let id = match self.0 {
+            KeyMaterial::Memory(ref key) => key.id(),
+            #[cfg(feature = "pkcs11")]
+            KeyMaterial::Token(ref key) => key.public_key().0.id(),
+        };
+        match id {
             Id::RSA => PrivateKeyType::Rsa,
             Id::EC => PrivateKeyType::Ec,
             _ => PrivateKeyType::Other,
         }
     }
+
+    /// Return public key of the key pair
+    pub fn public_key(&self) -> Result<PublicKey> {
+        match self.0 {
+            KeyMaterial::Memory(ref key) => PublicKey::from_der(&key.public_key_to_der()?),
+            #[cfg(feature = "pkcs11")]
+            KeyMaterial::Token(ref key) => Ok(key.public_key().clone()),
+        }
+    }
+
+    /// Return true if the key material can be exported, false for keys stored in a token
+    pub fn is_exportable(&self) -> bool {
+        matches!(self.0, KeyMaterial::Memory(_))
+    }
+
+    /// In-memory OpenSSL key, token keys cannot be exported
+    pub(crate) fn pkey(&self) -> Result<&PKey<Private>> {
+        match self.0 {
+            KeyMaterial::Memory(ref key) => Ok(key),
+            #[cfg(feature = "pkcs11")]
+            KeyMaterial::Token(_) => Err(PkiError::NonExportableKey),
+        }
+    }
 }
 
+#[cfg(not(feature = "pkcs11"))]
 impl From<PrivateKey> for PKey<Private> {
     fn from(key: PrivateKey) -> Self {
-        key.0
+        match key.0 {
+            KeyMaterial::Memory(key) => key,
+        }
+    }
+}
+
+/// Token keys cannot be converted, see [`PrivateKey::is_exportable`]
+#[cfg(feature = "pkcs11")]
+impl TryFrom<PrivateKey> for PKey<Private> {
+    type Error = PkiError;
+
+    fn try_from(key: PrivateKey) -> Result<Self> {
+        key.pkey().cloned()
     }
 }
 
 impl From<PKey<Private>> for PrivateKey {
     fn from(key: PKey<Private>) -> Self {
+        Self(KeyMaterial::Memory(key))
+    }
+}
+
+/// PublicKey represents a public key
+#[derive(Debug, Clone)]
+pub struct PublicKey(pub(crate) PKey<Public>);
+
+impl PublicKey {
+    /// Parse public key from the DER-encoded SubjectPublicKeyInfo
+    pub fn from_der(data: &[u8]) -> Result<Self> {
+        Ok(Self(PKey::public_key_from_der(data)?))
+    }
+
+    /// Convert public key to the DER-encoded SubjectPublicKeyInfo
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        Ok(self.0.public_key_to_der()?)
+    }
+
+    /// Parse public key from PEM format
+    pub fn from_pem(data: &[u8]) -> Result<Self> {
+        Ok(Self(PKey::public_key_from_pem(data)?))
+    }
+
+    /// Convert public key to PEM format
+    pub fn to_pem(&self) -> Result<Vec<u8>> {
+        Ok(self.0.public_key_to_pem()?)
+    }
+
+    /// Return number of bits in the public key
+    pub fn bits(&self) -> u32 {
+        self.0.bits()
+    }
+}
+
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.public_eq(&other.0)
+    }
+}
+
+impl Eq for PublicKey {}
+
+impl From<PublicKey> for PKey<Public> {
+    fn from(key: PublicKey) -> Self {
+        key.0
+    }
+}
+
+impl From<PKey<Public>> for PublicKey {
+    fn from(key: PKey<Public>) -> Self {
         Self(key)
     }
 }
 
+/// Password-encrypted private key in PKCS8 DER format
+#[derive(Debug, Clone)]
+pub struct EncryptedPrivateKey(pub(crate) Vec<u8>);
+
+impl EncryptedPrivateKey {
+    /// Create encrypted private key from the encrypted PKCS8 DER format.
+    /// The data is not validated until it is decrypted.
+    pub fn from_der(data: &[u8]) -> Self {
+        Self(data.to_vec())
+    }
+
+    /// Return encrypted PKCS8 DER representation
+    pub fn to_der(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Decrypt private key with a given password
+    pub fn decrypt(&self, password: &str) -> Result<PrivateKey> {
+        PrivateKey::from_pkcs8_der(&self.0, password)
+    }
+}
+
 /// X.509 certificate
 #[derive(Debug, Clone)]
 pub struct Certificate(pub(crate) X509);
@@ -185,9 +372,70 @@ impl Certificate {
     }
 
     /// Get certificate subject name
-    pub fn subject_name(&self) -> CertNameRef {
+    pub fn subject_name(&self) -> CertNameRef<'_> {
         CertNameRef(self.0.subject_name())
     }
+
+    /// Get certificate issuer name
+    pub fn issuer_name(&self) -> CertNameRef<'_> {
+        CertNameRef(self.0.issuer_name())
+    }
+
+    /// Returns true if the certificate is issued and signed by itself
+    pub fn is_self_signed(&self) -> bool {
+        self.0.issued(&self.0) == X509VerifyResult::OK
+            && self
+                .0
+                .public_key()
+                .and_then(|key| self.0.verify(&key))
+                .unwrap_or(false)
+    }
+
+    /// Get public key of the certificate
+    pub fn public_key(&self) -> Result<PublicKey> {
+        Ok(PublicKey(self.0.public_key()?))
+    }
+
+    /// Get start date of the certificate
+    pub fn not_before(&self) -> Result<CertTime> {
+        CertTime::from_asn1_time(self.0.not_before())
+    }
+
+    /// Get expiration date of the certificate
+    pub fn not_after(&self) -> Result<CertTime> {
+        CertTime::from_asn1_time(self.0.not_after())
+    }
+
+    /// Get key usage bits, None if the extension is absent
+    pub fn key_usage(&self) -> Result<Option<KeyUsage>> {
+        extension::cert_extension(&self.0, OID_KEY_USAGE)
+            .map(|ext| KeyUsage::decode(ext.value()))
+            .transpose()
+    }
+
+    /// Get extended key usage OIDs, None if the extension is absent
+    pub fn extended_key_usage(&self) -> Result<Option<Vec<String>>> {
+        extension::extended_key_usage(&self.0)
+    }
+
+    /// Get certificate serial number
+    pub fn serial_number(&self) -> Result<SerialNumber> {
+        let bn = self.0.serial_number().to_bn()?;
+        if bn.is_negative() {
+            return Err(PkiError::InvalidParameters);
+        }
+        SerialNumber::from_bytes(&bn.to_vec())
+    }
+
+    /// Get all certificate extensions in their original order
+    pub fn extensions(&self) -> Vec<Extension> {
+        extension::cert_extensions(&self.0)
+    }
+
+    /// Get certificate extension with a given OID
+    pub fn extension(&self, oid: &str) -> Option<Extension> {
+        extension::cert_extension(&self.0, oid)
+    }
 }
 
 impl From<Certificate> for X509 {
@@ -222,8 +470,18 @@ impl CertName {
         Ok(Self(name))
     }
 
+    /// Parse name from DER format
+    pub fn from_der(data: &[u8]) -> Result<Self> {
+        Ok(Self(X509Name::from_der(data)?))
+    }
+
+    /// Serialize name into DER format
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        Ok(self.0.to_der()?)
+    }
+
     /// Return entries iterator
-    pub fn entries(&self) -> CertNameEntries {
+    pub fn entries(&self) -> CertNameEntries<'_> {
         CertNameRef(self.0.as_ref()).entries()
     }
 }
@@ -249,6 +507,11 @@ impl<'a> CertNameRef<'a> {
     pub fn entries(&self) -> CertNameEntries<'a> {
         CertNameEntries(self.0.entries())
     }
+
+    /// Serialize name into DER format
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        Ok(self.0.to_der()?)
+    }
 }
 
 impl<'a> From<CertNameRef<'a>> for &'a X509NameRef {
@@ -263,18 +526,24 @@ impl<'a> From<&'a X509NameRef> for CertNameRef<'a> {
     }
 }
 
-/// X.509 name entries iterator
+/// X.509 name entries iterator: the attribute type as an OpenSSL short name or
+/// a dotted OID if the type is unknown, and the value
 pub struct CertNameEntries<'a>(X509NameEntries<'a>);
 
 impl<'a> Iterator for CertNameEntries<'a> {
-    type Item = (&'a str, Cow<'a, str>);
+    type Item = (Cow<'a, str>, Cow<'a, str>);
 
     fn next(&mut self) -> Option<Self::Item> {
         let entry = self.0.next()?;
-        Some((
-            entry.object().nid().short_name().expect("No short name"),
-            String::from_utf8_lossy(entry.data().as_slice()),
-        ))
+        let value = match entry.data().to_string() {
+            Ok(value) => Cow::Owned(value),
+            Err(_) => String::from_utf8_lossy(entry.data().as_slice()),
+        };
+        let field = match entry.object().nid().short_name() {
+            Ok(name) if entry.object().nid() != Nid::UNDEF => Cow::Borrowed(name),
+            _ => Cow::Owned(name::oid_string(entry.object())),
+        };
+        Some((field, value))
     }
 }
 
@@ -282,10 +551,12 @@ impl<'a> Iterator for CertNameEntries<'a> {
 pub struct KeyStore {
     private_key: PrivateKey,
     certs: Vec<Certificate>,
+    revocation_info: Option<RevocationInfo>,
 }
 
 impl KeyStore {
     /// Create new key store. The first certificate entry must be a leaf certificate.
+    /// The consistency of the key and certificates is not checked, see [`KeyStore::validate`].
     pub fn new<I>(key: PrivateKey, certs: I) -> Result<Self>
     where
         I: IntoIterator<Item = Certificate>,
@@ -297,11 +568,13 @@ impl KeyStore {
             Ok(Self {
                 private_key: key,
                 certs,
+                revocation_info: None,
             })
         }
     }
 
-    /// Load key store from the PKCS12/PFX file
+    /// Load key store from the PKCS12/PFX file.
+    /// The consistency of the key and certificates is not checked, see [`KeyStore::validate`].
     pub fn from_pkcs12(data: &[u8], password: &str) -> Result<Self> {
         let pkcs12 = Pkcs12::from_der(data)?;
         let parsed = pkcs12.parse2(password)?;
@@ -314,8 +587,9 @@ impl KeyStore {
         }
         if let Some(pkey) = parsed.pkey {
             Ok(Self {
-                private_key: PrivateKey(pkey),
+                private_key: pkey.into(),
                 certs,
+                revocation_info: None,
             })
         } else {
             Err(PkiError::MissingPrivateKey)
@@ -334,12 +608,13 @@ impl KeyStore {
         }
         builder
             .name(alias)
-            .pkey(&self.private_key.0)
+            .pkey(self.private_key.pkey()?)
             .cert(&self.certs[0].0);
         Ok(builder.build2(password)?.to_der()?)
     }
 
-    /// Load key store from PEM-encoded PKCS8 file which contains both private key and certificate chain
+    /// Load key store from PEM-encoded PKCS8 file which contains both private key and certificate chain.
+    /// The consistency of the key and certificates is not checked, see [`KeyStore::validate`].
     pub fn from_pkcs8(data: &[u8]) -> Result<Self> {
         let key = PrivateKey::from_pkcs8_pem(data)?;
         let certs = X509::stack_from_pem(data)?
@@ -349,6 +624,7 @@ impl KeyStore {
         Ok(Self {
             private_key: key,
             certs,
+            revocation_info: None,
         })
     }
 
@@ -357,7 +633,7 @@ impl KeyStore {
         let mut result = self.private_key.to_pkcs8_pem()?;
         for cert in &self.certs {
             let pem = cert.to_pem()?;
-            result.extend(pem.into_iter());
+            result.extend(pem);
         }
         Ok(result)
     }
@@ -371,4 +647,81 @@ impl KeyStore {
     pub fn certs(&self) -> &[Certificate] {
         &self.certs
     }
+
+    /// Return the leaf certificate, `None` if the key store has no certificates
+    pub fn leaf(&self) -> Option<&Certificate> {
+        self.certs.first()
+    }
+
+    /// Return the intermediate certificates: all certificates after the leaf which are not self-signed
+    pub fn intermediates(&self) -> Vec<&Certificate> {
+        self.certs
+            .get(1..)
+            .unwrap_or_default()
+            .iter()
+            .filter(|cert| !cert.is_self_signed())
+            .collect()
+    }
+
+    /// Return the self-signed root certificate if it is present in the chain
+    pub fn root(&self) -> Option<&Certificate> {
+        self.certs.iter().find(|cert| cert.is_self_signed())
+    }
+
+    /// Check that the private key matches the leaf certificate and every certificate
+    /// is issued by the next one: the issuer name, authority key identifier and signature
+    /// must match the issuer certificate. The last certificate is checked against itself
+    /// if its issuer and subject names are equal. The error reports the depth of the first
+    /// offending certificate. Validity periods and extensions are not checked,
+    /// use [`CertificateVerifier`](crate::CertificateVerifier) to validate the trust path.
+    pub fn validate(&self) -> Result<()> {
+        let mismatch = |depth: usize, reason| PkiError::InvalidKeyStore {
+            depth,
+            subject: self.certs[depth].subject_name().to_string(),
+            reason,
+        };
+
+        let leaf = self.certs.first().ok_or(PkiError::InvalidParameters)?;
+        if leaf.public_key()? != self.private_key.public_key()? {
+            return Err(mismatch(0, KeyStoreMismatch::PrivateKey));
+        }
+
+        for (depth, cert) in self.certs.iter().enumerate() {
+            let issuer = match self.certs.get(depth + 1) {
+                Some(issuer) => issuer,
+                None if cert.issuer_name() == cert.subject_name() => cert,
+                None => break,
+            };
+            if cert.issuer_name() != issuer.subject_name() {
+                return Err(mismatch(depth, KeyStoreMismatch::IssuerName));
+            }
+            if let (Some(key_id), Some(issuer_key_id)) =
+                (cert.0.authority_key_id(), issuer.0.subject_key_id())
+            {
+                if key_id.as_slice() != issuer_key_id.as_slice() {
+                    return Err(mismatch(depth, KeyStoreMismatch::KeyIdentifier));
+                }
+            }
+            let issuer_key = issuer.0.public_key()?;
+            if !cert.0.verify(&issuer_key).unwrap_or(false) {
+                return Err(mismatch(depth, KeyStoreMismatch::Signature));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get default revocation information for the certificates signed by this key store
+    pub fn revocation_info(&self) -> Option<&RevocationInfo> {
+        self.revocation_info.as_ref()
+    }
+
+    /// Set default revocation information for the certificates signed by this key store.
+    /// It is not persisted in the PKCS12 or PKCS8 formats.
+    pub fn set_revocation_info<R>(&mut self, info: R) -> &mut Self
+    where
+        R: Into<Option<RevocationInfo>>,
+    {
+        self.revocation_info = info.into();
+        self
+    }
 }
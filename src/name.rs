@@ -0,0 +1,961 @@
+//! Distinguished name parsing and formatting
+use std::{
+    ffi::c_int,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl::{
+    asn1::{Asn1Object, Asn1ObjectRef},
+    nid::Nid,
+    x509::{X509Name, X509NameEntryRef, X509NameRef},
+};
+
+use crate::{
+    der, ffi,
+    model::{CertName, CertNameRef, PkiError, Result},
+};
+
+/// Attribute names accepted case-insensitively in addition to the OpenSSL short and long names
+const ATTRIBUTE_ALIASES: &[(&str, Nid)] = &[
+    ("CN", Nid::COMMONNAME),
+    ("C", Nid::COUNTRYNAME),
+    ("L", Nid::LOCALITYNAME),
+    ("ST", Nid::STATEORPROVINCENAME),
+    ("S", Nid::STATEORPROVINCENAME),
+    ("O", Nid::ORGANIZATIONNAME),
+    ("OU", Nid::ORGANIZATIONALUNITNAME),
+    ("STREET", Nid::STREETADDRESS),
+    ("DC", Nid::DOMAINCOMPONENT),
+    ("UID", Nid::USERID),
+    ("E", Nid::PKCS9_EMAILADDRESS),
+    ("EMAIL", Nid::PKCS9_EMAILADDRESS),
+    ("EMAILADDRESS", Nid::PKCS9_EMAILADDRESS),
+    ("SERIALNUMBER", Nid::SERIALNUMBER),
+    ("SN", Nid::SURNAME),
+    ("GN", Nid::GIVENNAME),
+    ("GIVENNAME", Nid::GIVENNAME),
+    ("T", Nid::TITLE),
+    ("TITLE", Nid::TITLE),
+];
+
+/// ASN.1 string tags which can be rendered as text
+const TEXT_STRING_TAGS: &[c_int] = &[12, 18, 19, 20, 22, 26, 27, 28, 30];
+
+/// ASN.1 string tags which OpenSSL accepts as attribute values
+const VALUE_STRING_TAGS: &[c_int] = &[12, 18, 19, 20, 22, 28, 30];
+
+/// Distinguished name string format
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NameFormat {
+    /// RFC 4514 format with the most significant RDN last, for example `CN=host,O=Acme,C=US`
+    Rfc4514,
+    /// OpenSSL format with the most significant RDN first, for example `/C=US/O=Acme/CN=host`
+    OpenSsl,
+}
+
+/// Parsed attribute value
+pub(crate) enum RawValue {
+    /// Text value, the string type is selected by OpenSSL
+    Text(String),
+    /// Value with an explicit ASN.1 tag and content
+    Der { tag: c_int, content: Vec<u8> },
+}
+
+/// Parsed attribute type and value
+pub(crate) struct RawAttribute {
+    pub(crate) object: Asn1Object,
+    pub(crate) value: RawValue,
+}
+
+/// Build X.509 name from a list of RDNs, most significant RDN first
+pub(crate) fn build_name(rdns: &[Vec<RawAttribute>]) -> Result<X509Name> {
+    let name = X509Name::builder()?.build();
+    for rdn in rdns {
+        for (i, attr) in rdn.iter().enumerate() {
+            let (string_type, data) = match attr.value {
+                RawValue::Text(ref text) => (openssl_sys::MBSTRING_UTF8, text.as_bytes()),
+                RawValue::Der { tag, ref content } => (tag, content.as_slice()),
+            };
+            append_entry(&name, &attr.object, string_type, data, i == 0).map_err(|e| {
+                PkiError::InvalidName(format!(
+                    "invalid {} value: {}",
+                    attribute_type_name(&attr.object),
+                    e
+                ))
+            })?;
+        }
+    }
+    Ok(name)
+}
+
+/// Append an entry to the name, either as a new RDN or as a part of the last multi-valued RDN
+pub(crate) fn append_entry(
+    name: &X509Name,
+    object: &Asn1ObjectRef,
+    string_type: c_int,
+    data: &[u8],
+    new_rdn: bool,
+) -> Result<()> {
+    let len = c_int::try_from(data.len()).map_err(|_| PkiError::InvalidParameters)?;
+    // SAFETY: the name and object are valid, the data is copied into the new entry
+    let result = unsafe {
+        ffi::X509_NAME_add_entry_by_OBJ(
+            name.as_ptr(),
+            object.as_ptr(),
+            string_type,
+            data.as_ptr(),
+            len,
+            -1,
+            if new_rdn { 0 } else { -1 },
+        )
+    };
+    if result <= 0 {
+        Err(openssl::error::ErrorStack::get().into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Group name entries into RDNs, most significant RDN first
+pub(crate) fn name_rdns(name: &X509NameRef) -> Vec<Vec<&X509NameEntryRef>> {
+    let mut rdns: Vec<Vec<&X509NameEntryRef>> = Vec::new();
+    let mut last_set = None;
+    for entry in name.entries() {
+        // SAFETY: the entry is valid, the function only reads its RDN index
+        let set = unsafe { ffi::X509_NAME_ENTRY_set(entry.as_ptr()) };
+        match rdns.last_mut() {
+            Some(rdn) if last_set == Some(set) => rdn.push(entry),
+            _ => rdns.push(vec![entry]),
+        }
+        last_set = Some(set);
+    }
+    rdns
+}
+
+/// Return ASN.1 tag of the entry value
+pub(crate) fn entry_tag(entry: &X509NameEntryRef) -> c_int {
+    // SAFETY: the entry data is a valid ASN.1 string
+    unsafe { openssl_sys::ASN1_STRING_type(entry.data().as_ptr()) }
+}
+
+/// Return entry value as text if it is a string type which can be converted to UTF-8
+pub(crate) fn entry_text(entry: &X509NameEntryRef) -> Option<String> {
+    if TEXT_STRING_TAGS.contains(&entry_tag(entry)) {
+        entry.data().to_string().ok()
+    } else {
+        None
+    }
+}
+
+/// Return attribute type name: OpenSSL short name if known, dotted OID otherwise
+pub(crate) fn attribute_type_name(object: &Asn1ObjectRef) -> String {
+    match object.nid().short_name() {
+        Ok(name) if object.nid() != Nid::UNDEF => name.to_owned(),
+        _ => object.to_string(),
+    }
+}
+
+/// Resolve attribute type by OpenSSL short or long name, known alias or dotted OID
+pub(crate) fn attribute_object(name: &str) -> Result<Asn1Object> {
+    let name = name.trim();
+    let oid = name
+        .strip_prefix("OID.")
+        .or_else(|| name.strip_prefix("oid."))
+        .unwrap_or(name);
+    if oid.starts_with(|c: char| c.is_ascii_digit()) {
+        return oid
+            .split('.')
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| Asn1Object::from_str(oid).ok())
+            .flatten()
+            .ok_or_else(|| PkiError::InvalidName(format!("invalid OID: {}", oid)));
+    }
+    if let Some((_, nid)) = ATTRIBUTE_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+    {
+        return Ok(Asn1Object::from_str(nid.short_name()?)?);
+    }
+    if !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        if let Ok(object) = Asn1Object::from_str(name) {
+            return Ok(object);
+        }
+    }
+    Err(PkiError::InvalidName(format!(
+        "unknown attribute type: {}",
+        name
+    )))
+}
+
+fn hex_digit(c: char) -> Option<u8> {
+    c.to_digit(16).map(|d| d as u8)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> PkiError {
+        PkiError::InvalidName(format!(
+            "{} at position {} in \"{}\"",
+            message, self.pos, self.input
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn attribute_type(&mut self) -> Result<Asn1Object> {
+        self.skip_spaces();
+        let start = self.pos;
+        while !matches!(self.peek(), None | Some('=')) {
+            self.pos += 1;
+        }
+        if self.peek() != Some('=') {
+            return Err(self.error("expected '='"));
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        self.pos += 1;
+        attribute_object(&name)
+    }
+
+    fn is_rfc4514_separator(c: char) -> bool {
+        matches!(c, ',' | ';' | '+')
+    }
+
+    fn rfc4514_value(&mut self) -> Result<RawValue> {
+        self.skip_spaces();
+        match self.peek() {
+            Some('#') => self.hex_value(),
+            Some('"') => self.quoted_value(),
+            _ => self.string_value(Self::is_rfc4514_separator, true),
+        }
+    }
+
+    fn hex_value(&mut self) -> Result<RawValue> {
+        self.pos += 1;
+        let start = self.pos;
+        while self.peek().map(|c| c.is_ascii_hexdigit()).unwrap_or(false) {
+            self.pos += 1;
+        }
+        let digits = self.chars[start..self.pos].to_vec();
+        self.skip_spaces();
+        if digits.is_empty() || !digits.len().is_multiple_of(2) {
+            return Err(self.error("invalid hex value"));
+        }
+        let bytes: Vec<u8> = digits
+            .chunks(2)
+            .map(|pair| (hex_digit(pair[0]).unwrap() << 4) | hex_digit(pair[1]).unwrap())
+            .collect();
+        match der::read_tlv(&bytes) {
+            Ok((tag, content, rest))
+                if rest.is_empty() && VALUE_STRING_TAGS.contains(&(tag as c_int)) =>
+            {
+                Ok(RawValue::Der {
+                    tag: tag as c_int,
+                    content: content.to_vec(),
+                })
+            }
+            _ => Err(self.error("invalid DER value")),
+        }
+    }
+
+    fn quoted_value(&mut self) -> Result<RawValue> {
+        self.pos += 1;
+        let mut value = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated quoted value")),
+                Some('"') => break,
+                Some('\\') => self.escape(&mut value)?,
+                Some(c) => {
+                    value.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    self.pos += 1;
+                }
+            }
+        }
+        self.pos += 1;
+        self.skip_spaces();
+        self.text(value)
+    }
+
+    fn string_value(
+        &mut self,
+        is_separator: fn(char) -> bool,
+        hex_escapes: bool,
+    ) -> Result<RawValue> {
+        let mut value = Vec::new();
+        let mut significant_len = 0;
+        while let Some(c) = self.peek() {
+            if is_separator(c) {
+                break;
+            }
+            if c == '\\' {
+                if hex_escapes {
+                    self.escape(&mut value)?;
+                } else {
+                    self.pos += 1;
+                    let c = self.peek().ok_or_else(|| self.error("invalid escape"))?;
+                    value.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    self.pos += 1;
+                }
+                significant_len = value.len();
+            } else {
+                value.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                self.pos += 1;
+                if c != ' ' {
+                    significant_len = value.len();
+                }
+            }
+        }
+        value.truncate(significant_len);
+        self.text(value)
+    }
+
+    fn escape(&mut self, value: &mut Vec<u8>) -> Result<()> {
+        self.pos += 1;
+        let first = self.peek().ok_or_else(|| self.error("invalid escape"))?;
+        match (
+            hex_digit(first),
+            self.chars.get(self.pos + 1).copied().and_then(hex_digit),
+        ) {
+            (Some(hi), Some(lo)) => {
+                value.push((hi << 4) | lo);
+                self.pos += 2;
+            }
+            _ if matches!(
+                first,
+                '"' | '+' | ',' | ';' | '<' | '>' | '\\' | '#' | '=' | ' '
+            ) =>
+            {
+                value.push(first as u8);
+                self.pos += 1;
+            }
+            _ => return Err(self.error("invalid escape")),
+        }
+        Ok(())
+    }
+
+    fn text(&self, value: Vec<u8>) -> Result<RawValue> {
+        String::from_utf8(value)
+            .map(RawValue::Text)
+            .map_err(|_| self.error("invalid UTF-8 value"))
+    }
+
+    /// Parse RFC 4514 string, return RDNs with the most significant first
+    fn parse_rfc4514(mut self) -> Result<Vec<Vec<RawAttribute>>> {
+        let mut rdns = Vec::new();
+        let mut rdn = Vec::new();
+        if self.input.trim().is_empty() {
+            return Ok(rdns);
+        }
+        loop {
+            let object = self.attribute_type()?;
+            let value = self.rfc4514_value()?;
+            rdn.push(RawAttribute { object, value });
+            match self.peek() {
+                Some('+') => {}
+                Some(',') | Some(';') => rdns.push(std::mem::take(&mut rdn)),
+                None => {
+                    rdns.push(rdn);
+                    break;
+                }
+                Some(_) => return Err(self.error("unexpected character")),
+            }
+            self.pos += 1;
+        }
+        rdns.reverse();
+        Ok(rdns)
+    }
+
+    /// Parse OpenSSL `/type=value/type=value` string
+    fn parse_openssl(mut self) -> Result<Vec<Vec<RawAttribute>>> {
+        let mut rdns = Vec::new();
+        let mut rdn = Vec::new();
+        self.pos += 1;
+        if self.peek().is_none() {
+            return Ok(rdns);
+        }
+        loop {
+            let object = self.attribute_type()?;
+            let value = self.string_value(|c| c == '/' || c == '+', false)?;
+            rdn.push(RawAttribute { object, value });
+            match self.peek() {
+                Some('+') => {}
+                Some('/') => rdns.push(std::mem::take(&mut rdn)),
+                _ => {
+                    rdns.push(rdn);
+                    break;
+                }
+            }
+            self.pos += 1;
+        }
+        Ok(rdns)
+    }
+}
+
+/// Parse RFC 4514 or OpenSSL distinguished name string
+pub(crate) fn parse_name(s: &str) -> Result<Vec<Vec<RawAttribute>>> {
+    let parser = Parser::new(s.trim());
+    if s.trim_start().starts_with('/') {
+        parser.parse_openssl()
+    } else {
+        parser.parse_rfc4514()
+    }
+}
+
+fn escape_rfc4514(value: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    let last = value.chars().count().saturating_sub(1);
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => write!(f, "\\{}", c)?,
+            '#' if i == 0 => f.write_str("\\#")?,
+            ' ' if i == 0 || i == last => f.write_str("\\ ")?,
+            '\0' => f.write_str("\\00")?,
+            _ => write!(f, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+fn escape_openssl(value: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    for c in value.chars() {
+        if matches!(c, '/' | '+' | '\\') {
+            write!(f, "\\{}", c)?;
+        } else {
+            write!(f, "{}", c)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_entry(
+    entry: &X509NameEntryRef,
+    format: NameFormat,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    write!(f, "{}=", attribute_type_name(entry.object()))?;
+    match entry_text(entry) {
+        Some(text) if format == NameFormat::Rfc4514 => escape_rfc4514(&text, f),
+        Some(text) => escape_openssl(&text, f),
+        None => {
+            f.write_str("#")?;
+            let tag = entry_tag(entry) as u8;
+            for b in der::tlv(tag, entry.data().as_slice()) {
+                write!(f, "{:02x}", b)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// ASN.1 string type of a name attribute value
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum NameStringType {
+    /// UTF8String
+    Utf8,
+    /// PrintableString, restricted to `A-Z a-z 0-9 '()+,-./:=?` and space
+    Printable,
+    /// IA5String, restricted to ASCII
+    Ia5,
+    /// NumericString, restricted to digits and space
+    Numeric,
+    /// TeletexString, encoded as Latin-1
+    Teletex,
+    /// BMPString, encoded as UTF-16BE
+    Bmp,
+    /// UniversalString, encoded as UTF-32BE
+    Universal,
+}
+
+impl NameStringType {
+    fn tag(&self) -> c_int {
+        match self {
+            Self::Utf8 => openssl_sys::V_ASN1_UTF8STRING,
+            Self::Printable => openssl_sys::V_ASN1_PRINTABLESTRING,
+            Self::Ia5 => openssl_sys::V_ASN1_IA5STRING,
+            Self::Numeric => openssl_sys::V_ASN1_NUMERICSTRING,
+            Self::Teletex => openssl_sys::V_ASN1_T61STRING,
+            Self::Bmp => openssl_sys::V_ASN1_BMPSTRING,
+            Self::Universal => openssl_sys::V_ASN1_UNIVERSALSTRING,
+        }
+    }
+
+    fn from_tag(tag: c_int) -> Option<Self> {
+        [
+            Self::Utf8,
+            Self::Printable,
+            Self::Ia5,
+            Self::Numeric,
+            Self::Teletex,
+            Self::Bmp,
+            Self::Universal,
+        ]
+        .into_iter()
+        .find(|t| t.tag() == tag)
+    }
+
+    fn encode(&self, value: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Utf8 => Some(value.as_bytes().to_vec()),
+            Self::Printable => value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b))
+                .then(|| value.as_bytes().to_vec()),
+            Self::Ia5 => value.is_ascii().then(|| value.as_bytes().to_vec()),
+            Self::Numeric => value
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b == b' ')
+                .then(|| value.as_bytes().to_vec()),
+            Self::Teletex => value.chars().map(|c| u8::try_from(c).ok()).collect(),
+            Self::Bmp => value
+                .chars()
+                .map(|c| u16::try_from(c as u32).ok().map(u16::to_be_bytes))
+                .collect::<Option<Vec<_>>>()
+                .map(|v| v.concat()),
+            Self::Universal => Some(
+                value
+                    .chars()
+                    .flat_map(|c| (c as u32).to_be_bytes())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Name attribute for the [`CertNameBuilder`]
+#[derive(Debug, Clone)]
+pub struct NameAttribute {
+    field: String,
+    value: String,
+    string_type: Option<NameStringType>,
+}
+
+impl NameAttribute {
+    /// Create new attribute. The field is an OpenSSL short or long name, or a dotted OID
+    /// such as `2.5.4.3`. The string type is selected by OpenSSL unless specified explicitly.
+    pub fn new<F, V>(field: F, value: V) -> Self
+    where
+        F: AsRef<str>,
+        V: AsRef<str>,
+    {
+        Self {
+            field: field.as_ref().to_owned(),
+            value: value.as_ref().to_owned(),
+            string_type: None,
+        }
+    }
+
+    /// Specify explicit ASN.1 string type of the value
+    pub fn string_type(mut self, string_type: NameStringType) -> Self {
+        self.string_type = Some(string_type);
+        self
+    }
+
+    fn to_raw(&self) -> Result<RawAttribute> {
+        let object = attribute_object(&self.field)?;
+        let value = match self.string_type {
+            None => RawValue::Text(self.value.clone()),
+            Some(string_type) => RawValue::Der {
+                tag: string_type.tag(),
+                content: string_type.encode(&self.value).ok_or_else(|| {
+                    PkiError::InvalidName(format!(
+                        "value \"{}\" cannot be encoded as {:?}",
+                        self.value, string_type
+                    ))
+                })?,
+            },
+        };
+        Ok(RawAttribute { object, value })
+    }
+}
+
+impl<F, V> From<(F, V)> for NameAttribute
+where
+    F: AsRef<str>,
+    V: AsRef<str>,
+{
+    fn from((field, value): (F, V)) -> Self {
+        Self::new(field, value)
+    }
+}
+
+/// Builder for names with explicit attribute encodings and multi-valued RDNs
+#[derive(Debug, Clone, Default)]
+pub struct CertNameBuilder {
+    rdns: Vec<Vec<NameAttribute>>,
+}
+
+impl CertNameBuilder {
+    /// Create new empty name builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an attribute as a separate RDN
+    pub fn attribute<A>(&mut self, attribute: A) -> &mut Self
+    where
+        A: Into<NameAttribute>,
+    {
+        self.rdns.push(vec![attribute.into()]);
+        self
+    }
+
+    /// Append several attributes grouped into a single multi-valued RDN
+    pub fn multi_valued<I, A>(&mut self, attributes: I) -> &mut Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<NameAttribute>,
+    {
+        let rdn: Vec<_> = attributes.into_iter().map(Into::into).collect();
+        if !rdn.is_empty() {
+            self.rdns.push(rdn);
+        }
+        self
+    }
+
+    /// Create the name. RDNs are encoded in the order they were added, most significant first.
+    pub fn build(&self) -> Result<CertName> {
+        let rdns = self
+            .rdns
+            .iter()
+            .map(|rdn| rdn.iter().map(NameAttribute::to_raw).collect())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(CertName(build_name(&rdns)?))
+    }
+}
+
+/// Distinguished name formatter returned by [`CertNameRef::display`]
+pub struct CertNameDisplay<'a> {
+    name: &'a X509NameRef,
+    format: NameFormat,
+}
+
+impl fmt::Display for CertNameDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rdns = name_rdns(self.name);
+        let (rdn_separator, prefix) = match self.format {
+            NameFormat::Rfc4514 => {
+                rdns.reverse();
+                (",", "")
+            }
+            NameFormat::OpenSsl => ("/", "/"),
+        };
+        for (i, rdn) in rdns.iter().enumerate() {
+            f.write_str(if i == 0 { prefix } else { rdn_separator })?;
+            for (j, entry) in rdn.iter().enumerate() {
+                if j > 0 {
+                    f.write_str("+")?;
+                }
+                write_entry(entry, self.format, f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CertName {
+    /// Return a builder for names with explicit attribute encodings and multi-valued RDNs
+    pub fn builder() -> CertNameBuilder {
+        CertNameBuilder::new()
+    }
+
+    /// Parse distinguished name string.
+    /// RFC 4514 strings such as `CN=host,O=Acme\, Inc,C=US` are supported, including
+    /// escaped characters, hex-encoded `#` values and multi-valued RDNs joined with `+`.
+    /// Strings starting with `/` are parsed in the OpenSSL format, for example `/C=US/O=Acme/CN=host`.
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(Self(build_name(&parse_name(s)?)?))
+    }
+
+    /// Return a formatter for the name in a given format
+    pub fn display(&self, format: NameFormat) -> CertNameDisplay<'_> {
+        CertNameDisplay {
+            name: &self.0,
+            format,
+        }
+    }
+}
+
+impl FromStr for CertName {
+    type Err = PkiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Debug for CertName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CertName").field(&self.to_string()).finish()
+    }
+}
+
+impl fmt::Display for CertName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(NameFormat::Rfc4514).fmt(f)
+    }
+}
+
+impl<'a> CertNameRef<'a> {
+    /// Return a formatter for the name in a given format
+    pub fn display(&self, format: NameFormat) -> CertNameDisplay<'a> {
+        CertNameDisplay {
+            name: self.0,
+            format,
+        }
+    }
+}
+
+impl fmt::Display for CertNameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(NameFormat::Rfc4514).fmt(f)
+    }
+}
+
+/// Name attribute with its position and original encoding, returned by [`CertNameRef::attributes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertNameAttribute {
+    rdn_index: usize,
+    oid: String,
+    field: String,
+    tag: c_int,
+    data: Vec<u8>,
+    text: Option<String>,
+}
+
+impl CertNameAttribute {
+    /// Index of the RDN this attribute belongs to, most significant RDN first.
+    /// Attributes of a multi-valued RDN share the same index.
+    pub fn rdn_index(&self) -> usize {
+        self.rdn_index
+    }
+
+    /// Attribute type as a dotted OID, for example `2.5.4.3`
+    pub fn oid(&self) -> &str {
+        &self.oid
+    }
+
+    /// Attribute type as an OpenSSL short name if known, dotted OID otherwise
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// ASN.1 string type of the value, `None` for non-string values
+    pub fn string_type(&self) -> Option<NameStringType> {
+        NameStringType::from_tag(self.tag)
+    }
+
+    /// Value decoded to UTF-8, `None` if the value is not a string
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Raw value content without the ASN.1 tag and length
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Return dotted OID of the object
+pub(crate) fn oid_string(object: &Asn1ObjectRef) -> String {
+    let mut buf = [0u8; 128];
+    // SAFETY: the object is valid, OpenSSL writes at most buf.len() bytes including the terminator
+    let len = unsafe {
+        openssl_sys::OBJ_obj2txt(
+            buf.as_mut_ptr() as *mut _,
+            buf.len() as c_int,
+            object.as_ptr(),
+            1,
+        )
+    };
+    String::from_utf8_lossy(&buf[..(len.max(0) as usize).min(buf.len() - 1)]).into_owned()
+}
+
+/// Canonical attribute value used for comparison
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum CanonicalValue {
+    Text(String),
+    Binary(c_int, Vec<u8>),
+}
+
+/// Canonical name used for comparison: RDNs with sorted (OID, value) attributes
+type CanonicalName = Vec<Vec<(String, CanonicalValue)>>;
+
+/// Prepare string value for comparison according to RFC 5280 section 7.1:
+/// case folding, removal of the leading and trailing whitespace and
+/// compression of the internal whitespace to a single space.
+/// Unlike the full RFC 4518 preparation, no Unicode normalization or mapping is applied.
+fn canonical_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn canonical_name(name: &X509NameRef) -> CanonicalName {
+    name_rdns(name)
+        .into_iter()
+        .map(|rdn| {
+            let mut attrs: Vec<_> = rdn
+                .into_iter()
+                .map(|entry| {
+                    let value = match entry_text(entry) {
+                        Some(text) => CanonicalValue::Text(canonical_text(&text)),
+                        None => CanonicalValue::Binary(
+                            entry_tag(entry),
+                            entry.data().as_slice().to_vec(),
+                        ),
+                    };
+                    (oid_string(entry.object()), value)
+                })
+                .collect();
+            attrs.sort();
+            attrs
+        })
+        .collect()
+}
+
+fn openssl_name_hash(name: &X509NameRef) -> u32 {
+    // SAFETY: the name is valid, the default library context and properties are used
+    #[cfg(openssl_3_0)]
+    let hash = unsafe {
+        ffi::X509_NAME_hash_ex(
+            name.as_ptr(),
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null_mut(),
+        )
+    };
+    // SAFETY: the name is valid
+    #[cfg(not(openssl_3_0))]
+    let hash = unsafe { ffi::X509_NAME_hash(name.as_ptr()) };
+    hash as u32
+}
+
+impl<'a> CertNameRef<'a> {
+    /// Return all attributes with their RDN positions and original encodings,
+    /// most significant RDN first
+    pub fn attributes(&self) -> Vec<CertNameAttribute> {
+        name_rdns(self.0)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(rdn_index, rdn)| {
+                rdn.into_iter().map(move |entry| CertNameAttribute {
+                    rdn_index,
+                    oid: oid_string(entry.object()),
+                    field: attribute_type_name(entry.object()),
+                    tag: entry_tag(entry),
+                    data: entry.data().as_slice().to_vec(),
+                    text: entry_text(entry),
+                })
+            })
+            .collect()
+    }
+
+    /// Compare names according to RFC 5280 section 7.1.
+    /// String values are compared case-insensitively regardless of their ASN.1 string type,
+    /// with insignificant whitespace ignored. Attributes of multi-valued RDNs may appear in any order.
+    /// This is a simplified form of the RFC 4518 string preparation: values are only lowercased
+    /// and their whitespace collapsed, without Unicode normalization or character mapping.
+    pub fn matches(&self, other: &CertNameRef) -> bool {
+        canonical_name(self.0) == canonical_name(other.0)
+    }
+
+    /// Return OpenSSL-compatible name hash, as used by `openssl x509 -subject_hash`
+    /// and c_rehash-style certificate directories
+    pub fn openssl_hash(&self) -> u32 {
+        openssl_name_hash(self.0)
+    }
+}
+
+impl CertName {
+    /// Return all attributes with their RDN positions and original encodings,
+    /// most significant RDN first
+    pub fn attributes(&self) -> Vec<CertNameAttribute> {
+        self.as_name_ref().attributes()
+    }
+
+    /// Compare names according to RFC 5280 section 7.1, see [`CertNameRef::matches`]
+    pub fn matches(&self, other: &CertNameRef) -> bool {
+        self.as_name_ref().matches(other)
+    }
+
+    /// Return OpenSSL-compatible name hash, see [`CertNameRef::openssl_hash`]
+    pub fn openssl_hash(&self) -> u32 {
+        self.as_name_ref().openssl_hash()
+    }
+
+    /// Return a reference to this name
+    pub fn as_name_ref(&self) -> CertNameRef<'_> {
+        CertNameRef(&self.0)
+    }
+}
+
+/// Equality as defined by [`CertNameRef::matches`]: lowercased values with collapsed whitespace,
+/// not the full RFC 4518 string preparation
+impl PartialEq for CertNameRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.matches(other)
+    }
+}
+
+impl Eq for CertNameRef<'_> {}
+
+/// Hash of the same canonical form as used by [`PartialEq`]
+impl Hash for CertNameRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        canonical_name(self.0).hash(state);
+    }
+}
+
+/// Equality as defined by [`CertNameRef::matches`]: lowercased values with collapsed whitespace,
+/// not the full RFC 4518 string preparation
+impl PartialEq for CertName {
+    fn eq(&self, other: &Self) -> bool {
+        self.matches(&other.as_name_ref())
+    }
+}
+
+impl PartialEq<CertNameRef<'_>> for CertName {
+    fn eq(&self, other: &CertNameRef<'_>) -> bool {
+        self.matches(other)
+    }
+}
+
+impl PartialEq<CertName> for CertNameRef<'_> {
+    fn eq(&self, other: &CertName) -> bool {
+        self.matches(&other.as_name_ref())
+    }
+}
+
+impl Eq for CertName {}
+
+/// Hash of the same canonical form as used by [`PartialEq`]
+impl Hash for CertName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_name_ref().hash(state);
+    }
+}
@@ -0,0 +1,366 @@
+//! Keys stored in PKCS#11 tokens
+//!
+//! [`PrivateKey::from_pkcs11`], [`PrivateKey::new_pkcs11_rsa`] and [`PrivateKey::new_pkcs11_ec`]
+//! return a [`PrivateKey`] which refers to the token key, it signs certificates
+//! with [`CertificateBuilder`](crate::CertificateBuilder) and certificate requests with
+//! [`CertificateRequestBuilder`](crate::CertificateRequestBuilder), and acts as a
+//! certificate authority in a [`KeyStore`](crate::KeyStore).
+//! The private key never leaves the token, exporting it fails with [`PkiError::NonExportableKey`].
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, OnceLock, Weak},
+};
+
+use cryptoki::{
+    context::{CInitializeArgs, CInitializeFlags, Pkcs11},
+    error::{Error, RvError},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    slot::Slot,
+    types::{AuthPin, Ulong},
+};
+use openssl::{
+    bn::{BigNum, BigNumContext},
+    ec::{EcGroup, EcKey, EcPoint},
+    ecdsa::EcdsaSig,
+    hash::hash,
+    nid::Nid,
+    pkey::{Id, PKey},
+    rsa::Rsa,
+};
+
+use crate::{
+    der,
+    model::{KeyMaterial, PkiError, PrivateKey, PublicKey, Result},
+    signer::SignatureAlgorithm,
+};
+
+const OID_PRIME256V1: &str = "1.2.840.10045.3.1.7";
+const OID_SECP384R1: &str = "1.3.132.0.34";
+
+/// Location of a key pair in a PKCS#11 token
+#[derive(Debug)]
+pub struct Pkcs11Config {
+    module: PathBuf,
+    slot: u64,
+    label: String,
+    pin: Option<AuthPin>,
+}
+
+impl Pkcs11Config {
+    /// Create a new configuration from the PKCS#11 module path, slot ID and key label
+    pub fn new<P: AsRef<Path>>(module: P, slot: u64, label: &str) -> Self {
+        Self {
+            module: module.as_ref().to_owned(),
+            slot,
+            label: label.to_owned(),
+            pin: None,
+        }
+    }
+
+    /// Specify the user PIN. If omitted the session is not logged in.
+    pub fn pin(mut self, pin: &str) -> Self {
+        self.pin = Some(AuthPin::from(pin));
+        self
+    }
+
+    /// Get the PKCS#11 module path
+    pub fn module(&self) -> &Path {
+        &self.module
+    }
+
+    /// Get the slot ID
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// Get the key label
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    // the session is declared after the context so it is dropped first
+    fn open_session(&self) -> Result<(Context, Session)> {
+        let context = Context::get(&self.module)?;
+        let session = context
+            .0
+            .pkcs11
+            .open_rw_session(Slot::try_from(self.slot)?)?;
+        if let Some(ref pin) = self.pin {
+            match session.login(UserType::User, Some(pin)) {
+                Ok(()) | Err(Error::Pkcs11(RvError::UserAlreadyLoggedIn, _)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok((context, session))
+    }
+
+    fn find_object(&self, session: &Session, class: ObjectClass) -> Result<ObjectHandle> {
+        let template = [
+            Attribute::Class(class),
+            Attribute::Label(self.label.as_bytes().to_vec()),
+        ];
+        match session.find_objects(&template)?.as_slice() {
+            [handle] => Ok(*handle),
+            [] => Err(PkiError::MissingPrivateKey),
+            _ => Err(PkiError::InvalidParameters),
+        }
+    }
+}
+
+/// Initialized PKCS#11 module shared by all token keys with the same module path.
+/// The module is finalized when the last key using it is dropped.
+struct Context(Arc<ContextInner>);
+
+struct ContextInner {
+    module: PathBuf,
+    pkcs11: Pkcs11,
+    // false if the module was already initialized by someone else in the process
+    finalize: bool,
+}
+
+fn contexts() -> MutexGuard<'static, HashMap<PathBuf, Weak<ContextInner>>> {
+    static CONTEXTS: OnceLock<Mutex<HashMap<PathBuf, Weak<ContextInner>>>> = OnceLock::new();
+    CONTEXTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+impl Context {
+    fn get(module: &Path) -> Result<Self> {
+        let mut contexts = contexts();
+        if let Some(inner) = contexts.get(module).and_then(Weak::upgrade) {
+            return Ok(Self(inner));
+        }
+        let pkcs11 = Pkcs11::new(module)?;
+        let finalize =
+            match pkcs11.initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK)) {
+                Ok(()) => true,
+                Err(Error::Pkcs11(RvError::CryptokiAlreadyInitialized, _)) => false,
+                Err(e) => return Err(e.into()),
+            };
+        let inner = Arc::new(ContextInner {
+            module: module.to_owned(),
+            pkcs11,
+            finalize,
+        });
+        contexts.insert(module.to_owned(), Arc::downgrade(&inner));
+        Ok(Self(inner))
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // the registry lock keeps the module from being reopened while it is finalized
+        let mut contexts = contexts();
+        if Arc::strong_count(&self.0) == 1 {
+            contexts.remove(&self.0.module);
+            if self.0.finalize {
+                let _ = self.0.pkcs11.clone().finalize();
+            }
+        }
+    }
+}
+
+impl PrivateKey {
+    /// Open an existing key pair with the configured label in the token
+    pub fn from_pkcs11(config: &Pkcs11Config) -> Result<Self> {
+        let (context, session) = config.open_session()?;
+        let handle = config.find_object(&session, ObjectClass::PRIVATE_KEY)?;
+        let public_handle = config.find_object(&session, ObjectClass::PUBLIC_KEY)?;
+        TokenKey::open(session, context, handle, public_handle)
+    }
+
+    /// Generate RSA key pair in the token.
+    /// Fails with [`PkiError::DuplicateLabel`] if the token already has an object with the label.
+    pub fn new_pkcs11_rsa(config: &Pkcs11Config, bits: u32) -> Result<Self> {
+        let public_template = [
+            Attribute::ModulusBits(Ulong::try_from(bits as usize)?),
+            Attribute::PublicExponent(vec![0x01, 0x00, 0x01]),
+        ];
+        generate(config, &Mechanism::RsaPkcsKeyPairGen, &public_template)
+    }
+
+    /// Generate EC key pair in the token, supported sizes are 256 and 384 bits.
+    /// Fails with [`PkiError::DuplicateLabel`] if the token already has an object with the label.
+    pub fn new_pkcs11_ec(config: &Pkcs11Config, bits: u32) -> Result<Self> {
+        let curve = match bits {
+            256 => OID_PRIME256V1,
+            384 => OID_SECP384R1,
+            _ => return Err(PkiError::InvalidParameters),
+        };
+        let public_template = [Attribute::EcParams(der::oid(curve)?)];
+        generate(config, &Mechanism::EccKeyPairGen, &public_template)
+    }
+}
+
+fn generate(
+    config: &Pkcs11Config,
+    mechanism: &Mechanism,
+    public_template: &[Attribute],
+) -> Result<PrivateKey> {
+    let (context, session) = config.open_session()?;
+    let label = Attribute::Label(config.label.as_bytes().to_vec());
+    // the key is looked up by label, a second key pair would make it ambiguous
+    if !session
+        .find_objects(std::slice::from_ref(&label))?
+        .is_empty()
+    {
+        return Err(PkiError::DuplicateLabel(config.label.clone()));
+    }
+
+    let mut public_template = public_template.to_vec();
+    public_template.extend([
+        label.clone(),
+        Attribute::Token(true),
+        Attribute::Private(false),
+        Attribute::Verify(true),
+    ]);
+    let private_template = [
+        label,
+        Attribute::Token(true),
+        Attribute::Private(true),
+        Attribute::Sensitive(true),
+        Attribute::Extractable(false),
+        Attribute::Sign(true),
+    ];
+
+    let (public_handle, handle) =
+        session.generate_key_pair(mechanism, &public_template, &private_template)?;
+    TokenKey::open(session, context, handle, public_handle)
+}
+
+/// Private key handle in an open token session
+pub(crate) struct TokenKey {
+    // the session is closed before the module is finalized
+    session: Mutex<Session>,
+    _context: Context,
+    handle: ObjectHandle,
+    public_key: PublicKey,
+}
+
+impl TokenKey {
+    fn open(
+        session: Session,
+        context: Context,
+        handle: ObjectHandle,
+        public_handle: ObjectHandle,
+    ) -> Result<PrivateKey> {
+        let public_key = token_public_key(&session, public_handle)?;
+        let key = Self {
+            session: Mutex::new(session),
+            _context: context,
+            handle,
+            public_key,
+        };
+        Ok(PrivateKey(KeyMaterial::Token(Arc::new(key))))
+    }
+
+    pub(crate) fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// SHA-256 for RSA and P-256 keys, SHA-384 for P-384 keys
+    pub(crate) fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        match self.public_key.0.id() {
+            Id::RSA => Ok(SignatureAlgorithm::RsaSha256),
+            Id::EC => match self.public_key.0.ec_key()?.group().curve_name() {
+                Some(Nid::X9_62_PRIME256V1) => Ok(SignatureAlgorithm::EcdsaSha256),
+                Some(Nid::SECP384R1) => Ok(SignatureAlgorithm::EcdsaSha384),
+                _ => Err(PkiError::InvalidParameters),
+            },
+            _ => Err(PkiError::InvalidParameters),
+        }
+    }
+
+    pub(crate) fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        // a session must not be used by several threads at once
+        let session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        match self.algorithm()? {
+            SignatureAlgorithm::RsaSha256 => {
+                Ok(session.sign(&Mechanism::Sha256RsaPkcs, self.handle, tbs)?)
+            }
+            algorithm => {
+                // the raw mechanism is supported by most tokens, the signature is r || s
+                let digest = algorithm.digest().ok_or(PkiError::InvalidParameters)?;
+                let digest = hash(digest, tbs)?;
+                let signature = session.sign(&Mechanism::Ecdsa, self.handle, &digest)?;
+                let (r, s) = signature.split_at(signature.len() / 2);
+                let signature = EcdsaSig::from_private_components(
+                    BigNum::from_slice(r)?,
+                    BigNum::from_slice(s)?,
+                )?;
+                Ok(signature.to_der()?)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TokenKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenKey")
+            .field("handle", &self.handle)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Read the public key object from the token
+fn token_public_key(session: &Session, handle: ObjectHandle) -> Result<PublicKey> {
+    let attributes = session.get_attributes(
+        handle,
+        &[
+            AttributeType::KeyType,
+            AttributeType::Modulus,
+            AttributeType::PublicExponent,
+            AttributeType::EcParams,
+            AttributeType::EcPoint,
+        ],
+    )?;
+
+    let mut key_type = None;
+    let (mut modulus, mut exponent, mut params, mut point) = (None, None, None, None);
+    for attribute in attributes {
+        match attribute {
+            Attribute::KeyType(value) => key_type = Some(value),
+            Attribute::Modulus(value) => modulus = Some(value),
+            Attribute::PublicExponent(value) => exponent = Some(value),
+            Attribute::EcParams(value) => params = Some(value),
+            Attribute::EcPoint(value) => point = Some(value),
+            _ => {}
+        }
+    }
+
+    let key = match (key_type, modulus, exponent, params, point) {
+        (Some(KeyType::RSA), Some(modulus), Some(exponent), _, _) => {
+            PKey::from_rsa(Rsa::from_public_components(
+                BigNum::from_slice(&modulus)?,
+                BigNum::from_slice(&exponent)?,
+            )?)?
+        }
+        (Some(KeyType::EC), _, _, Some(params), Some(point)) => {
+            let nid = if params == der::oid(OID_PRIME256V1)? {
+                Nid::X9_62_PRIME256V1
+            } else if params == der::oid(OID_SECP384R1)? {
+                Nid::SECP384R1
+            } else {
+                return Err(PkiError::InvalidParameters);
+            };
+            // the point is DER-encoded OCTET STRING, some tokens return the raw value
+            let point = match der::read_tlv(&point) {
+                Ok((der::TAG_OCTET_STRING, value, [])) => value,
+                _ => &point,
+            };
+            let group = EcGroup::from_curve_name(nid)?;
+            let mut ctx = BigNumContext::new()?;
+            let point = EcPoint::from_bytes(&group, point, &mut ctx)?;
+            PKey::from_ec_key(EcKey::from_public_key(&group, &point)?)?
+        }
+        _ => return Err(PkiError::InvalidParameters),
+    };
+    Ok(PublicKey(key))
+}
@@ -0,0 +1,198 @@
+//! Certificate signing requests
+use std::{fmt, ptr};
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl::{
+    error::ErrorStack,
+    stack::Stack,
+    x509::{X509Req, X509ReqRef},
+};
+
+use crate::{
+    extension::{self, Extension, OID_SUBJECT_ALT_NAME},
+    ffi,
+    model::{CertName, CertNameRef, PkiError, PublicKey, Result},
+    signer::{self, Signer},
+};
+
+/// PKCS#10 certificate signing request
+pub struct CertificateRequest(pub(crate) X509Req);
+
+impl CertificateRequest {
+    /// Parse certificate request from DER format
+    pub fn from_der(data: &[u8]) -> Result<Self> {
+        Ok(Self(X509Req::from_der(data)?))
+    }
+
+    /// Parse certificate request from PEM format
+    pub fn from_pem(data: &[u8]) -> Result<Self> {
+        Ok(Self(X509Req::from_pem(data)?))
+    }
+
+    /// Convert certificate request to DER format
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        Ok(self.0.to_der()?)
+    }
+
+    /// Convert certificate request to PEM format
+    pub fn to_pem(&self) -> Result<Vec<u8>> {
+        Ok(self.0.to_pem()?)
+    }
+
+    /// Get requested subject name
+    pub fn subject_name(&self) -> CertNameRef<'_> {
+        CertNameRef(self.0.subject_name())
+    }
+
+    /// Get requested public key
+    pub fn public_key(&self) -> Result<PublicKey> {
+        Ok(PublicKey(self.0.public_key()?))
+    }
+
+    /// Get requested extensions
+    pub fn extensions(&self) -> Vec<Extension> {
+        self.0
+            .extensions()
+            .map(|extensions| extensions.iter().map(Extension::from_openssl).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get requested extension with a given OID
+    pub fn extension(&self, oid: &str) -> Option<Extension> {
+        self.extensions().into_iter().find(|ext| ext.oid() == oid)
+    }
+
+    /// Verify the request signature with the requested public key
+    pub fn verify(&self) -> Result<bool> {
+        Ok(self.0.verify(self.0.public_key()?.as_ref())?)
+    }
+}
+
+impl Clone for CertificateRequest {
+    fn clone(&self) -> Self {
+        // SAFETY: the request pointer is valid, the duplicate is owned by the new instance
+        unsafe {
+            Self(X509Req::from_ptr(openssl_sys::X509_REQ_dup(
+                self.0.as_ptr(),
+            )))
+        }
+    }
+}
+
+impl fmt::Debug for CertificateRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CertificateRequest")
+            .field(&self.subject_name().to_string())
+            .finish()
+    }
+}
+
+/// Encode the to-be-signed part of the certificate request
+fn tbs_request(req: &X509ReqRef) -> Result<Vec<u8>> {
+    // SAFETY: the request is valid and the output buffer has the size reported by the first call
+    unsafe {
+        let len = ffi::i2d_re_X509_REQ_tbs(req.as_ptr(), ptr::null_mut());
+        if len <= 0 {
+            return Err(ErrorStack::get().into());
+        }
+        let mut result = vec![0u8; len as usize];
+        let mut out = result.as_mut_ptr();
+        if ffi::i2d_re_X509_REQ_tbs(req.as_ptr(), &mut out) != len {
+            return Err(ErrorStack::get().into());
+        }
+        Ok(result)
+    }
+}
+
+/// Certificate request builder is used to create PKCS#10 requests
+/// signed by an in-memory or external key
+#[derive(Default)]
+pub struct CertificateRequestBuilder<'a> {
+    signer: Option<&'a dyn Signer>,
+    subject: Option<CertName>,
+    alt_names: Vec<String>,
+    extensions: Vec<Extension>,
+}
+
+impl<'a> CertificateRequestBuilder<'a> {
+    /// Create new certificate request builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify the key of the request: a [`PrivateKey`](crate::PrivateKey),
+    /// a [`KeyStore`](crate::KeyStore) or any other [`Signer`] implementation.
+    /// The certificates of the signer are ignored.
+    pub fn signer<S: Signer + 'a>(&mut self, signer: &'a S) -> &mut Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Specify requested subject name
+    pub fn subject(&mut self, subject: CertName) -> &mut Self {
+        self.subject = Some(subject);
+        self
+    }
+
+    /// Specify requested subject alternative names
+    pub fn alt_names<I, T>(&mut self, alt_names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.alt_names = alt_names
+            .into_iter()
+            .map(|s| s.as_ref().to_owned())
+            .collect();
+        self
+    }
+
+    /// Add custom requested extension
+    pub fn extension(&mut self, extension: Extension) -> &mut Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Create the certificate request
+    pub fn build(&self) -> Result<CertificateRequest> {
+        let signer = self.signer.ok_or(PkiError::MissingPrivateKey)?;
+        let public_key = signer.public_key()?;
+
+        let mut builder = X509Req::builder()?;
+        builder.set_version(0)?;
+        builder.set_pubkey(&public_key.0)?;
+        if let Some(ref subject) = self.subject {
+            builder.set_subject_name(&subject.0)?;
+        }
+
+        let mut extensions = Vec::new();
+        if !self.alt_names.is_empty() {
+            let context = builder.x509v3_context(None);
+            extensions.push(extension::subject_alt_name(&self.alt_names, &context)?);
+        }
+        extensions.extend(
+            self.extensions
+                .iter()
+                .filter(|ext| self.alt_names.is_empty() || ext.oid() != OID_SUBJECT_ALT_NAME)
+                .cloned(),
+        );
+        if !extensions.is_empty() {
+            let mut stack = Stack::new()?;
+            for ext in extensions {
+                stack.push(ext.to_openssl()?)?;
+            }
+            builder.add_extensions(&stack)?;
+        }
+
+        let algorithm = signer.algorithm()?;
+        let tbs = tbs_request(&builder.build())?;
+        let signature = signer.sign(&tbs)?;
+        let request =
+            CertificateRequest::from_der(&signer::signed_data(tbs, algorithm, signature)?)?;
+
+        if !request.0.verify(&public_key.0)? {
+            return Err(PkiError::InvalidParameters);
+        }
+        Ok(request)
+    }
+}
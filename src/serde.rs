@@ -0,0 +1,443 @@
+//! Serde support for the model types, enabled with the `serde` feature.
+//!
+//! By default [`Certificate`] is serialized as a PEM string, [`CertName`] as an RFC 4514 string
+//! and [`EncryptedPrivateKey`] as an `ENCRYPTED PRIVATE KEY` PEM string.
+//! Alternative representations can be selected with `#[serde(with = "...")]`:
+//!
+//! * [`pem`] - PEM string
+//! * [`der_base64`] - base64-encoded DER, also keeps the exact string types of [`CertName`] attributes
+//! * [`structured`] - structured certificate description with the embedded DER
+//!
+//! Unencrypted private keys and key stores are never serialized implicitly,
+//! the [`private_key`] and [`key_store`] modules must be used explicitly.
+//! [`key_store::EncryptedKeyStore`] keeps the private key of a key store password-protected.
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Issued {
+//!     #[serde(with = "pki::serde::der_base64")]
+//!     cert: Certificate,
+//!     key: EncryptedPrivateKey,
+//! }
+//! ```
+use openssl::{base64, pkey::PKey};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::model::{
+    CertName, Certificate, EncryptedPrivateKey, KeyStore, PkiError, PrivateKey, Result,
+};
+
+const PEM_LINE_LENGTH: usize = 64;
+
+/// Types which can be represented as DER
+pub trait DerEncoded: Sized {
+    /// Encode into DER format
+    fn encode_der(&self) -> Result<Vec<u8>>;
+
+    /// Decode from DER format
+    fn decode_der(data: &[u8]) -> Result<Self>;
+}
+
+/// Types which can be represented as PEM
+pub trait PemEncoded: Sized {
+    /// Encode into PEM format
+    fn encode_pem(&self) -> Result<Vec<u8>>;
+
+    /// Decode from PEM format
+    fn decode_pem(data: &[u8]) -> Result<Self>;
+}
+
+impl DerEncoded for Certificate {
+    fn encode_der(&self) -> Result<Vec<u8>> {
+        self.to_der()
+    }
+
+    fn decode_der(data: &[u8]) -> Result<Self> {
+        Self::from_der(data)
+    }
+}
+
+impl PemEncoded for Certificate {
+    fn encode_pem(&self) -> Result<Vec<u8>> {
+        self.to_pem()
+    }
+
+    fn decode_pem(data: &[u8]) -> Result<Self> {
+        Self::from_pem(data)
+    }
+}
+
+impl DerEncoded for CertName {
+    fn encode_der(&self) -> Result<Vec<u8>> {
+        self.to_der()
+    }
+
+    fn decode_der(data: &[u8]) -> Result<Self> {
+        Self::from_der(data)
+    }
+}
+
+impl DerEncoded for EncryptedPrivateKey {
+    fn encode_der(&self) -> Result<Vec<u8>> {
+        Ok(self.to_der().to_vec())
+    }
+
+    fn decode_der(data: &[u8]) -> Result<Self> {
+        Ok(Self::from_der(data))
+    }
+}
+
+impl PemEncoded for EncryptedPrivateKey {
+    fn encode_pem(&self) -> Result<Vec<u8>> {
+        Ok(pem_encode("ENCRYPTED PRIVATE KEY", self.to_der()).into_bytes())
+    }
+
+    fn decode_pem(data: &[u8]) -> Result<Self> {
+        Ok(Self::from_der(&pem_decode("ENCRYPTED PRIVATE KEY", data)?))
+    }
+}
+
+fn pem_encode(label: &str, der: &[u8]) -> String {
+    let encoded = base64::encode_block(der);
+    let mut result = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(PEM_LINE_LENGTH) {
+        result.push_str(&String::from_utf8_lossy(line));
+        result.push('\n');
+    }
+    result.push_str(&format!("-----END {}-----\n", label));
+    result
+}
+
+fn pem_decode(label: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let text = std::str::from_utf8(data).map_err(|_| PkiError::InvalidParameters)?;
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let start = text.find(&begin).ok_or(PkiError::InvalidParameters)? + begin.len();
+    let stop = text[start..]
+        .find(&end)
+        .ok_or(PkiError::InvalidParameters)?
+        + start;
+    let body: String = text[start..stop].split_whitespace().collect();
+    Ok(base64::decode_block(&body)?)
+}
+
+fn to_ser_error<E: serde::ser::Error>(e: PkiError) -> E {
+    E::custom(e)
+}
+
+fn to_de_error<E: de::Error>(e: PkiError) -> E {
+    E::custom(e)
+}
+
+/// PEM string representation
+pub mod pem {
+    use super::*;
+
+    /// Serialize value as a PEM string
+    pub fn serialize<T, S>(value: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        T: PemEncoded,
+        S: Serializer,
+    {
+        let pem = value.encode_pem().map_err(to_ser_error)?;
+        serializer.serialize_str(&String::from_utf8_lossy(&pem))
+    }
+
+    /// Deserialize value from a PEM string
+    pub fn deserialize<'de, T, D>(deserializer: D) -> std::result::Result<T, D::Error>
+    where
+        T: PemEncoded,
+        D: Deserializer<'de>,
+    {
+        let pem = String::deserialize(deserializer)?;
+        T::decode_pem(pem.as_bytes()).map_err(to_de_error)
+    }
+}
+
+/// Base64-encoded DER representation
+pub mod der_base64 {
+    use super::*;
+
+    /// Serialize value as a base64-encoded DER string
+    pub fn serialize<T, S>(value: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        T: DerEncoded,
+        S: Serializer,
+    {
+        let der = value.encode_der().map_err(to_ser_error)?;
+        serializer.serialize_str(&base64::encode_block(&der))
+    }
+
+    /// Deserialize value from a base64-encoded DER string
+    pub fn deserialize<'de, T, D>(deserializer: D) -> std::result::Result<T, D::Error>
+    where
+        T: DerEncoded,
+        D: Deserializer<'de>,
+    {
+        let encoded = String::deserialize(deserializer)?;
+        let der = base64::decode_block(&encoded).map_err(|e| to_de_error(e.into()))?;
+        T::decode_der(&der).map_err(to_de_error)
+    }
+}
+
+/// Structured certificate representation.
+///
+/// The certificate is serialized as an object with the subject, issuer, serial number
+/// and validity period for readability, plus the base64-encoded DER which is the only
+/// field used for deserialization.
+pub mod structured {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct CertificateInfo {
+        #[serde(default, skip_deserializing)]
+        subject: Option<CertName>,
+        #[serde(default, skip_deserializing)]
+        issuer: Option<CertName>,
+        #[serde(default, skip_deserializing)]
+        serial_number: String,
+        #[serde(default, skip_deserializing)]
+        not_before: String,
+        #[serde(default, skip_deserializing)]
+        not_after: String,
+        #[serde(with = "super::der_base64")]
+        der: Certificate,
+    }
+
+    fn to_info(cert: &Certificate) -> Result<CertificateInfo> {
+        Ok(CertificateInfo {
+            subject: Some(CertName(cert.0.subject_name().to_owned()?)),
+            issuer: Some(CertName(cert.0.issuer_name().to_owned()?)),
+            serial_number: cert.0.serial_number().to_bn()?.to_hex_str()?.to_string(),
+            not_before: cert.0.not_before().to_string(),
+            not_after: cert.0.not_after().to_string(),
+            der: cert.clone(),
+        })
+    }
+
+    /// Serialize certificate as a structured object
+    pub fn serialize<S>(cert: &Certificate, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        to_info(cert).map_err(to_ser_error)?.serialize(serializer)
+    }
+
+    /// Deserialize certificate from a structured object
+    pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<Certificate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(CertificateInfo::deserialize(deserializer)?.der)
+    }
+}
+
+/// Opt-in serialization of unencrypted private keys in PKCS8 format
+pub mod private_key {
+    use super::*;
+
+    /// Unencrypted PKCS8 PEM string representation
+    pub mod pem {
+        use super::*;
+
+        /// Serialize private key as an unencrypted PKCS8 PEM string
+        pub fn serialize<S>(key: &PrivateKey, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let pem = key.to_pkcs8_pem().map_err(to_ser_error)?;
+            serializer.serialize_str(&String::from_utf8_lossy(&pem))
+        }
+
+        /// Deserialize private key from an unencrypted PKCS8 PEM string
+        pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<PrivateKey, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let pem = String::deserialize(deserializer)?;
+            PrivateKey::from_pkcs8_pem(pem.as_bytes()).map_err(to_de_error)
+        }
+    }
+
+    /// Unencrypted base64-encoded PKCS8 DER representation
+    pub mod der_base64 {
+        use super::*;
+
+        /// Serialize private key as an unencrypted base64-encoded PKCS8 DER string
+        pub fn serialize<S>(key: &PrivateKey, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let der = key
+                .pkey()
+                .map_err(to_ser_error)?
+                .private_key_to_pkcs8()
+                .map_err(|e| to_ser_error(e.into()))?;
+            serializer.serialize_str(&base64::encode_block(&der))
+        }
+
+        /// Deserialize private key from an unencrypted base64-encoded PKCS8 DER string
+        pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<PrivateKey, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let encoded = String::deserialize(deserializer)?;
+            let der = base64::decode_block(&encoded).map_err(|e| to_de_error(e.into()))?;
+            let key = PKey::private_key_from_pkcs8(&der).map_err(|e| to_de_error(e.into()))?;
+            Ok(key.into())
+        }
+    }
+}
+
+/// Serialization of key stores: opt-in `with` modules for unencrypted private keys
+/// and the password-protected [`EncryptedKeyStore`](key_store::EncryptedKeyStore)
+pub mod key_store {
+    use super::*;
+
+    /// Password-protected key store, serialized as a structured object with the
+    /// `ENCRYPTED PRIVATE KEY` PEM private key and a list of PEM certificates
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct EncryptedKeyStore {
+        private_key: EncryptedPrivateKey,
+        certs: Vec<Certificate>,
+    }
+
+    impl EncryptedKeyStore {
+        /// Encrypt the private key of the key store with a password
+        pub fn new(store: &KeyStore, password: &str) -> Result<Self> {
+            Ok(Self {
+                private_key: store.private_key().encrypt(password)?,
+                certs: store.certs().to_vec(),
+            })
+        }
+
+        /// Decrypt the private key with a password and return the key store
+        pub fn decrypt(&self, password: &str) -> Result<KeyStore> {
+            KeyStore::new(self.private_key.decrypt(password)?, self.certs.clone())
+        }
+
+        /// Return the certificate chain, which is not encrypted
+        pub fn certs(&self) -> &[Certificate] {
+            &self.certs
+        }
+    }
+
+    /// PEM string with the unencrypted PKCS8 private key followed by the certificate chain,
+    /// as produced by [`KeyStore::to_pkcs8`]
+    pub mod pkcs8_pem {
+        use super::*;
+
+        /// Serialize key store as a PKCS8 PEM string
+        pub fn serialize<S>(store: &KeyStore, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let pem = store.to_pkcs8().map_err(to_ser_error)?;
+            serializer.serialize_str(&String::from_utf8_lossy(&pem))
+        }
+
+        /// Deserialize key store from a PKCS8 PEM string
+        pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<KeyStore, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let pem = String::deserialize(deserializer)?;
+            KeyStore::from_pkcs8(pem.as_bytes()).map_err(to_de_error)
+        }
+    }
+
+    /// Structured object with the unencrypted PKCS8 PEM private key and a list of PEM certificates
+    pub mod structured {
+        use super::*;
+
+        #[derive(Serialize)]
+        struct KeyStoreRef<'a> {
+            #[serde(with = "crate::serde::private_key::pem")]
+            private_key: &'a PrivateKey,
+            certs: &'a [Certificate],
+        }
+
+        #[derive(Deserialize)]
+        struct KeyStoreOwned {
+            #[serde(with = "crate::serde::private_key::pem")]
+            private_key: PrivateKey,
+            certs: Vec<Certificate>,
+        }
+
+        /// Serialize key store as a structured object
+        pub fn serialize<S>(store: &KeyStore, serializer: S) -> std::result::Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            KeyStoreRef {
+                private_key: store.private_key(),
+                certs: store.certs(),
+            }
+            .serialize(serializer)
+        }
+
+        /// Deserialize key store from a structured object
+        pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<KeyStore, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let owned = KeyStoreOwned::deserialize(deserializer)?;
+            KeyStore::new(owned.private_key, owned.certs).map_err(to_de_error)
+        }
+    }
+}
+
+impl Serialize for Certificate {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        pem::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Certificate {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        pem::deserialize(deserializer)
+    }
+}
+
+impl Serialize for EncryptedPrivateKey {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        pem::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptedPrivateKey {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        pem::deserialize(deserializer)
+    }
+}
+
+impl Serialize for CertName {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CertName {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        CertName::parse(&name).map_err(to_de_error)
+    }
+}
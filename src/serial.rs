@@ -0,0 +1,126 @@
+//! Certificate serial numbers
+use std::fmt;
+
+use openssl::{asn1::Asn1Integer, bn::BigNum, rand::rand_bytes};
+
+use crate::model::{PkiError, Result};
+
+/// Maximum length of the serial number in octets as defined in RFC 5280
+pub const MAX_SERIAL_NUMBER_LENGTH: usize = 20;
+
+/// Length of the randomly generated serial numbers in octets
+pub const RANDOM_SERIAL_NUMBER_LENGTH: usize = 16;
+
+/// Certificate serial number: a positive integer of up to 20 octets
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SerialNumber(Vec<u8>);
+
+impl SerialNumber {
+    /// Create serial number from the big-endian unsigned bytes.
+    /// The number must be positive and fit into 20 octets of the DER encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        let bytes = &bytes[skip..];
+        let encoded_len = bytes.len() + usize::from(bytes.first().is_some_and(|b| b & 0x80 != 0));
+        if bytes.is_empty() || encoded_len > MAX_SERIAL_NUMBER_LENGTH {
+            Err(PkiError::InvalidParameters)
+        } else {
+            Ok(Self(bytes.to_vec()))
+        }
+    }
+
+    /// Create serial number from the hex string
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let bn = BigNum::from_hex_str(hex)?;
+        if bn.is_negative() {
+            return Err(PkiError::InvalidParameters);
+        }
+        Self::from_bytes(&bn.to_vec())
+    }
+
+    /// Generate a random serial number from the cryptographically secure generator.
+    /// The number is 16 octets long with 126 random bits: the top bit is cleared to keep it positive
+    /// and the next one is set to keep the full length.
+    pub fn random() -> Result<Self> {
+        let mut bytes = [0u8; RANDOM_SERIAL_NUMBER_LENGTH];
+        loop {
+            rand_bytes(&mut bytes)?;
+            // keep the full length and a positive encoding
+            bytes[0] = (bytes[0] & 0x7f) | 0x40;
+            if let Ok(serial) = Self::from_bytes(&bytes) {
+                return Ok(serial);
+            }
+        }
+    }
+
+    /// Big-endian unsigned bytes of the serial number
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Serial number from the integer value, zero is not rejected
+    pub(crate) fn from_u128(value: u128) -> Self {
+        let bytes = value.to_be_bytes();
+        let skip = bytes
+            .iter()
+            .take_while(|b| **b == 0)
+            .count()
+            .min(bytes.len() - 1);
+        Self(bytes[skip..].to_vec())
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.0.iter().all(|b| *b == 0)
+    }
+
+    pub(crate) fn to_asn1_integer(&self) -> Result<Asn1Integer> {
+        Ok(Asn1Integer::from_bn(BigNum::from_slice(&self.0)?.as_ref())?)
+    }
+}
+
+impl TryFrom<u64> for SerialNumber {
+    type Error = PkiError;
+
+    /// Zero is rejected, the serial number must be positive
+    fn try_from(value: u64) -> Result<Self> {
+        Self::try_from(u128::from(value))
+    }
+}
+
+impl TryFrom<u128> for SerialNumber {
+    type Error = PkiError;
+
+    /// Zero is rejected, the serial number must be positive
+    fn try_from(value: u128) -> Result<Self> {
+        if value == 0 {
+            Err(PkiError::InvalidParameters)
+        } else {
+            Ok(Self::from_u128(value))
+        }
+    }
+}
+
+impl fmt::Display for SerialNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in &self.0 {
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Source of serial numbers for the issued certificates
+pub trait SerialAllocator {
+    /// Allocate serial number for the next certificate
+    fn allocate(&self) -> Result<SerialNumber>;
+}
+
+/// Default allocator which generates random 16-octet serial numbers with 126 bits of entropy
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomSerialAllocator;
+
+impl SerialAllocator for RandomSerialAllocator {
+    fn allocate(&self) -> Result<SerialNumber> {
+        SerialNumber::random()
+    }
+}
@@ -0,0 +1,275 @@
+//! Certificate signers
+use std::{ffi::CString, ptr, sync::Mutex};
+
+use foreign_types::ForeignTypeRef;
+use openssl::{error::ErrorStack, hash::MessageDigest, pkey::Id, sign, x509::X509Ref};
+use openssl_sys::V_ASN1_NULL;
+
+use crate::{
+    der,
+    extension::RevocationInfo,
+    ffi,
+    model::{Certificate, KeyMaterial, KeyStore, PkiError, PrivateKey, PublicKey, Result},
+};
+
+/// Signature algorithm used for the issued certificates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SignatureAlgorithm {
+    RsaSha256,
+    RsaSha384,
+    RsaSha512,
+    EcdsaSha256,
+    EcdsaSha384,
+    EcdsaSha512,
+    Ed25519,
+}
+
+impl SignatureAlgorithm {
+    /// Get the algorithm OID
+    pub fn oid(&self) -> &'static str {
+        match self {
+            Self::RsaSha256 => "1.2.840.113549.1.1.11",
+            Self::RsaSha384 => "1.2.840.113549.1.1.12",
+            Self::RsaSha512 => "1.2.840.113549.1.1.13",
+            Self::EcdsaSha256 => "1.2.840.10045.4.3.2",
+            Self::EcdsaSha384 => "1.2.840.10045.4.3.3",
+            Self::EcdsaSha512 => "1.2.840.10045.4.3.4",
+            Self::Ed25519 => "1.3.101.112",
+        }
+    }
+
+    /// Returns true if the algorithm identifier has NULL parameters
+    pub(crate) fn has_null_parameters(&self) -> bool {
+        matches!(self, Self::RsaSha256 | Self::RsaSha384 | Self::RsaSha512)
+    }
+
+    /// Encode the AlgorithmIdentifier structure
+    pub(crate) fn algorithm_identifier(&self) -> Result<Vec<u8>> {
+        let mut content = der::oid(self.oid())?;
+        if self.has_null_parameters() {
+            content.extend(der::tlv(der::TAG_NULL, &[]));
+        }
+        Ok(der::tlv(der::TAG_SEQUENCE, &content))
+    }
+
+    pub(crate) fn digest(&self) -> Option<MessageDigest> {
+        match self {
+            Self::RsaSha256 | Self::EcdsaSha256 => Some(MessageDigest::sha256()),
+            Self::RsaSha384 | Self::EcdsaSha384 => Some(MessageDigest::sha384()),
+            Self::RsaSha512 | Self::EcdsaSha512 => Some(MessageDigest::sha512()),
+            Self::Ed25519 => None,
+        }
+    }
+}
+
+/// Encode the to-be-signed part of the certificate with a given signature algorithm
+pub(crate) fn tbs_certificate(cert: &X509Ref, algorithm: SignatureAlgorithm) -> Result<Vec<u8>> {
+    let oid = CString::new(algorithm.oid()).map_err(|_| PkiError::InvalidParameters)?;
+    let ptype = if algorithm.has_null_parameters() {
+        V_ASN1_NULL
+    } else {
+        ffi::V_ASN1_UNDEF
+    };
+    // SAFETY: the algorithm identifier is owned by the certificate which is not shared yet,
+    // the object ownership is transferred to the algorithm identifier
+    unsafe {
+        let object = openssl_sys::OBJ_txt2obj(oid.as_ptr(), 1);
+        if object.is_null() {
+            return Err(ErrorStack::get().into());
+        }
+        let alg = ffi::X509_get0_tbs_sigalg(cert.as_ptr()) as *mut _;
+        if ffi::X509_ALGOR_set0(alg, object, ptype, ptr::null_mut()) <= 0 {
+            openssl_sys::ASN1_OBJECT_free(object);
+            return Err(ErrorStack::get().into());
+        }
+        let len = ffi::i2d_re_X509_tbs(cert.as_ptr(), ptr::null_mut());
+        if len <= 0 {
+            return Err(ErrorStack::get().into());
+        }
+        let mut result = vec![0u8; len as usize];
+        let mut out = result.as_mut_ptr();
+        if ffi::i2d_re_X509_tbs(cert.as_ptr(), &mut out) != len {
+            return Err(ErrorStack::get().into());
+        }
+        Ok(result)
+    }
+}
+
+/// Encode the signed structure from the to-be-signed data and the signature value
+pub(crate) fn signed_data(
+    tbs: Vec<u8>,
+    algorithm: SignatureAlgorithm,
+    signature: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let mut bit_string = vec![0];
+    bit_string.extend(signature);
+    let mut content = tbs;
+    content.extend(algorithm.algorithm_identifier()?);
+    content.extend(der::tlv(der::TAG_BIT_STRING, &bit_string));
+    Ok(der::tlv(der::TAG_SEQUENCE, &content))
+}
+
+/// Certificate signer: the issuing CA key which may reside outside of the process,
+/// for example in an HSM, KMS or a remote signing service.
+/// See [`CertificateBuilder::external_signer`](crate::CertificateBuilder::external_signer).
+pub trait Signer {
+    /// Signing certificate followed by its issuer chain.
+    /// Empty if the signer is used to create a self-signed certificate.
+    fn certs(&self) -> &[Certificate];
+
+    /// Public key of the signing key
+    fn public_key(&self) -> Result<PublicKey>;
+
+    /// Signature algorithm
+    fn algorithm(&self) -> Result<SignatureAlgorithm>;
+
+    /// Sign the DER-encoded to-be-signed data returning the signature value.
+    /// ECDSA signatures must be DER-encoded as defined in RFC 3279.
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>>;
+
+    /// Revocation information added to the issued certificates by default
+    fn revocation_info(&self) -> Option<&RevocationInfo> {
+        None
+    }
+}
+
+impl<T: Signer + ?Sized> Signer for Box<T> {
+    fn certs(&self) -> &[Certificate] {
+        (**self).certs()
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        (**self).public_key()
+    }
+
+    fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        (**self).algorithm()
+    }
+
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        (**self).sign(tbs)
+    }
+
+    fn revocation_info(&self) -> Option<&RevocationInfo> {
+        (**self).revocation_info()
+    }
+}
+
+/// Private key signer which creates self-signed certificates.
+/// RSA and EC keys use SHA-256 digest, token keys sign inside the token.
+impl Signer for PrivateKey {
+    fn certs(&self) -> &[Certificate] {
+        &[]
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        PrivateKey::public_key(self)
+    }
+
+    fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        match self.0 {
+            KeyMaterial::Memory(ref key) => match key.id() {
+                Id::RSA => Ok(SignatureAlgorithm::RsaSha256),
+                Id::EC => Ok(SignatureAlgorithm::EcdsaSha256),
+                Id::ED25519 => Ok(SignatureAlgorithm::Ed25519),
+                _ => Err(PkiError::InvalidParameters),
+            },
+            #[cfg(feature = "pkcs11")]
+            KeyMaterial::Token(ref key) => key.algorithm(),
+        }
+    }
+
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        match self.0 {
+            KeyMaterial::Memory(ref key) => {
+                let mut signer = match self.algorithm()?.digest() {
+                    Some(digest) => sign::Signer::new(digest, key)?,
+                    None => sign::Signer::new_without_digest(key)?,
+                };
+                Ok(signer.sign_oneshot_to_vec(tbs)?)
+            }
+            #[cfg(feature = "pkcs11")]
+            KeyMaterial::Token(ref key) => key.sign(tbs),
+        }
+    }
+}
+
+/// In-memory signer which uses the private key and certificate chain of the key store
+impl Signer for KeyStore {
+    fn certs(&self) -> &[Certificate] {
+        KeyStore::certs(self)
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        self.private_key().public_key()
+    }
+
+    fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        self.private_key().algorithm()
+    }
+
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        self.private_key().sign(tbs)
+    }
+
+    fn revocation_info(&self) -> Option<&RevocationInfo> {
+        KeyStore::revocation_info(self)
+    }
+}
+
+/// Mock signer which records the to-be-signed data of every request
+/// and delegates signing to the inner signer
+pub struct RecordingSigner<S> {
+    inner: S,
+    requests: Mutex<Vec<Vec<u8>>>,
+}
+
+impl<S: Signer> RecordingSigner<S> {
+    /// Create a new recording signer
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Return the to-be-signed data of all requests in the order of arrival
+    pub fn requests(&self) -> Vec<Vec<u8>> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Return the inner signer
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: Signer> Signer for RecordingSigner<S> {
+    fn certs(&self) -> &[Certificate] {
+        self.inner.certs()
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        self.inner.public_key()
+    }
+
+    fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        self.inner.algorithm()
+    }
+
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(tbs.to_vec());
+        self.inner.sign(tbs)
+    }
+
+    fn revocation_info(&self) -> Option<&RevocationInfo> {
+        self.inner.revocation_info()
+    }
+}
@@ -0,0 +1,231 @@
+//! Certificate key usage profiles
+use std::{fmt, ops::BitOr};
+
+use crate::{
+    der,
+    extension::{OID_EMAIL_PROTECTION, OID_OCSP_SIGNING, OID_TIME_STAMPING},
+    model::{CertUsage, Result},
+};
+
+/// Set of the key usage bits as defined in RFC 5280
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeyUsage(u16);
+
+impl KeyUsage {
+    /// Verify digital signatures other than certificates and CRLs
+    pub const DIGITAL_SIGNATURE: KeyUsage = KeyUsage(1 << 0);
+    /// Verify signatures which provide a non-repudiation service, also known as contentCommitment
+    pub const NON_REPUDIATION: KeyUsage = KeyUsage(1 << 1);
+    /// Encipher private or secret keys, e.g. RSA key transport
+    pub const KEY_ENCIPHERMENT: KeyUsage = KeyUsage(1 << 2);
+    /// Encipher raw user data without an intermediate symmetric key
+    pub const DATA_ENCIPHERMENT: KeyUsage = KeyUsage(1 << 3);
+    /// Use the key in a key agreement protocol, e.g. ECDH
+    pub const KEY_AGREEMENT: KeyUsage = KeyUsage(1 << 4);
+    /// Verify signatures on certificates, requires a CA certificate
+    pub const KEY_CERT_SIGN: KeyUsage = KeyUsage(1 << 5);
+    /// Verify signatures on certificate revocation lists
+    pub const CRL_SIGN: KeyUsage = KeyUsage(1 << 6);
+    /// Only encipher data during key agreement, together with keyAgreement
+    pub const ENCIPHER_ONLY: KeyUsage = KeyUsage(1 << 7);
+    /// Only decipher data during key agreement, together with keyAgreement
+    pub const DECIPHER_ONLY: KeyUsage = KeyUsage(1 << 8);
+
+    const NAMES: [&'static str; 9] = [
+        "digitalSignature",
+        "nonRepudiation",
+        "keyEncipherment",
+        "dataEncipherment",
+        "keyAgreement",
+        "keyCertSign",
+        "cRLSign",
+        "encipherOnly",
+        "decipherOnly",
+    ];
+
+    /// Empty key usage set
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns true if no bits are set
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if all bits of the other set are present in this one
+    pub fn contains(&self, other: KeyUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn bits(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::NAMES.len()).filter(|bit| self.0 & (1 << bit) != 0)
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        der::tlv(der::TAG_BIT_STRING, &der::encode_named_bits(self.bits()))
+    }
+
+    pub(crate) fn decode(value: &[u8]) -> Result<Self> {
+        let content = der::DerReader::new(value).read_tag(der::TAG_BIT_STRING)?;
+        Ok(Self(
+            der::decode_named_bits(content)?
+                .into_iter()
+                .filter(|bit| *bit < Self::NAMES.len())
+                .fold(0, |acc, bit| acc | (1 << bit)),
+        ))
+    }
+}
+
+impl BitOr for KeyUsage {
+    type Output = KeyUsage;
+
+    fn bitor(self, rhs: KeyUsage) -> KeyUsage {
+        KeyUsage(self.0 | rhs.0)
+    }
+}
+
+impl fmt::Display for KeyUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self.bits().map(|bit| Self::NAMES[bit]).collect::<Vec<_>>();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+/// Composable certificate usage: key usage bits, extended key usage OIDs and their criticality.
+/// Presets are available via the [`CertUsage`] conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageProfile {
+    ca: bool,
+    key_usage: KeyUsage,
+    key_usage_critical: bool,
+    extended_usage: Vec<String>,
+    extended_usage_critical: bool,
+}
+
+impl Default for UsageProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageProfile {
+    /// Create an empty end-entity profile without key usage and extended key usage
+    pub fn new() -> Self {
+        Self {
+            ca: false,
+            key_usage: KeyUsage::empty(),
+            key_usage_critical: false,
+            extended_usage: Vec::new(),
+            extended_usage_critical: false,
+        }
+    }
+
+    /// Time stamping authority profile as required by RFC 3161
+    pub fn time_stamping() -> Self {
+        Self::new()
+            .key_usage(KeyUsage::DIGITAL_SIGNATURE | KeyUsage::NON_REPUDIATION)
+            .key_usage_critical(true)
+            .extended_usage(OID_TIME_STAMPING)
+            .extended_usage_critical(true)
+    }
+
+    /// Delegated OCSP responder profile as defined in RFC 6960
+    pub fn ocsp_signing() -> Self {
+        Self::new()
+            .key_usage(KeyUsage::DIGITAL_SIGNATURE)
+            .key_usage_critical(true)
+            .extended_usage(OID_OCSP_SIGNING)
+    }
+
+    /// S/MIME email protection profile
+    pub fn email_protection() -> Self {
+        Self::new()
+            .key_usage(KeyUsage::DIGITAL_SIGNATURE | KeyUsage::KEY_ENCIPHERMENT)
+            .key_usage_critical(true)
+            .extended_usage(OID_EMAIL_PROTECTION)
+    }
+
+    /// Mark the certificate as a certificate authority
+    pub fn ca(mut self, ca: bool) -> Self {
+        self.ca = ca;
+        self
+    }
+
+    /// Set key usage bits, an empty set omits the extension
+    pub fn key_usage(mut self, key_usage: KeyUsage) -> Self {
+        self.key_usage = key_usage;
+        self
+    }
+
+    /// Set criticality of the key usage extension, default is false
+    pub fn key_usage_critical(mut self, critical: bool) -> Self {
+        self.key_usage_critical = critical;
+        self
+    }
+
+    /// Add extended key usage OID
+    pub fn extended_usage<S: AsRef<str>>(mut self, oid: S) -> Self {
+        let oid = oid.as_ref();
+        if !self.extended_usage.iter().any(|o| o == oid) {
+            self.extended_usage.push(oid.to_owned());
+        }
+        self
+    }
+
+    /// Set criticality of the extended key usage extension, default is false
+    pub fn extended_usage_critical(mut self, critical: bool) -> Self {
+        self.extended_usage_critical = critical;
+        self
+    }
+
+    /// Returns true if this is a certificate authority profile
+    pub fn is_ca(&self) -> bool {
+        self.ca
+    }
+
+    /// Get key usage bits
+    pub fn key_usages(&self) -> KeyUsage {
+        self.key_usage
+    }
+
+    /// Get extended key usage OIDs
+    pub fn extended_usages(&self) -> &[String] {
+        &self.extended_usage
+    }
+
+    /// Returns true if the key usage extension is critical
+    pub fn is_key_usage_critical(&self) -> bool {
+        self.key_usage_critical
+    }
+
+    /// Returns true if the extended key usage extension is critical
+    pub fn is_extended_usage_critical(&self) -> bool {
+        self.extended_usage_critical
+    }
+}
+
+impl From<CertUsage> for UsageProfile {
+    fn from(usage: CertUsage) -> Self {
+        let profile = match usage {
+            CertUsage::CA => Self::new()
+                .ca(true)
+                .key_usage(KeyUsage::KEY_CERT_SIGN | KeyUsage::CRL_SIGN),
+            CertUsage::TlsServer | CertUsage::TlsClient | CertUsage::TlsServerAndClient => {
+                Self::new().key_usage(
+                    KeyUsage::DIGITAL_SIGNATURE
+                        | KeyUsage::NON_REPUDIATION
+                        | KeyUsage::KEY_ENCIPHERMENT
+                        | KeyUsage::DATA_ENCIPHERMENT,
+                )
+            }
+            CertUsage::CodeSign => {
+                Self::new().key_usage(KeyUsage::DIGITAL_SIGNATURE | KeyUsage::NON_REPUDIATION)
+            }
+        };
+        usage
+            .extended_usage_oids()
+            .iter()
+            .fold(profile, |profile, oid| profile.extended_usage(oid))
+    }
+}
@@ -0,0 +1,226 @@
+//! Certificate validity time
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use openssl::asn1::{Asn1Time, Asn1TimeRef};
+
+use crate::model::{PkiError, Result};
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Point in time used for the certificate validity period, with one second precision.
+///
+/// Unlike [`SystemTime`] it covers the full X.509 date range from year 0 to 9999, including the
+/// dates before 1970. Dates in the 1950..2049 range are encoded as UTCTime, all others as
+/// GeneralizedTime, as required by RFC 5280.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CertTime(i64);
+
+impl CertTime {
+    /// The "no well-defined expiration date" value 99991231235959Z as defined in RFC 5280
+    pub const NO_EXPIRY: CertTime = CertTime(253_402_300_799);
+
+    /// Earliest time which can be encoded in the certificate, 00000101000000Z
+    pub const MIN: CertTime = CertTime(-62_167_219_200);
+
+    /// Current time
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Create time from seconds since the Unix epoch, negative for the times before 1970
+    pub fn from_unix(secs: i64) -> Result<Self> {
+        if (Self::MIN.0..=Self::NO_EXPIRY.0).contains(&secs) {
+            Ok(Self(secs))
+        } else {
+            Err(PkiError::InvalidParameters)
+        }
+    }
+
+    /// Create UTC time from the calendar date and time of day
+    pub fn from_ymd_hms(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Result<Self> {
+        if !(0..=9999).contains(&year)
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(PkiError::InvalidParameters);
+        }
+        let days = days_from_civil(year as i64, month as i64, day as i64);
+        Ok(Self(
+            days * SECS_PER_DAY + (hour * 3600 + minute * 60 + second) as i64,
+        ))
+    }
+
+    /// Seconds since the Unix epoch, negative for the times before 1970
+    pub fn unix_timestamp(&self) -> i64 {
+        self.0
+    }
+
+    /// Returns true if this is the "no well-defined expiration date" value
+    pub fn is_no_expiry(&self) -> bool {
+        *self == Self::NO_EXPIRY
+    }
+
+    /// Add seconds to the time
+    pub fn add_secs(&self, secs: i64) -> Result<Self> {
+        Self::from_unix(
+            self.0
+                .checked_add(secs)
+                .ok_or(PkiError::InvalidParameters)?,
+        )
+    }
+
+    /// Add days to the time
+    pub fn add_days(&self, days: i64) -> Result<Self> {
+        self.add_secs(
+            days.checked_mul(SECS_PER_DAY)
+                .ok_or(PkiError::InvalidParameters)?,
+        )
+    }
+
+    /// Convert to [`SystemTime`]
+    pub fn to_system_time(&self) -> SystemTime {
+        if self.0 >= 0 {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(self.0 as u64)
+        } else {
+            SystemTime::UNIX_EPOCH - Duration::from_secs(self.0.unsigned_abs())
+        }
+    }
+
+    /// Calendar date and time of day as (year, month, day, hour, minute, second)
+    pub fn to_ymd_hms(&self) -> (i32, u32, u32, u32, u32, u32) {
+        let days = self.0.div_euclid(SECS_PER_DAY);
+        let secs = self.0.rem_euclid(SECS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        (year, month, day, secs / 3600, secs / 60 % 60, secs % 60)
+    }
+
+    pub(crate) fn to_asn1_time(self) -> Result<Asn1Time> {
+        let (year, month, day, hour, minute, second) = self.to_ymd_hms();
+        let time = if (1950..2050).contains(&year) {
+            format!(
+                "{:02}{:02}{:02}{:02}{:02}{:02}Z",
+                year % 100,
+                month,
+                day,
+                hour,
+                minute,
+                second
+            )
+        } else {
+            format!(
+                "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+                year, month, day, hour, minute, second
+            )
+        };
+        Ok(Asn1Time::from_str(&time)?)
+    }
+
+    pub(crate) fn from_asn1_time(time: &Asn1TimeRef) -> Result<Self> {
+        let diff = Asn1Time::from_unix(0)?.diff(time)?;
+        Ok(Self(diff.days as i64 * SECS_PER_DAY + diff.secs as i64))
+    }
+}
+
+impl From<SystemTime> for CertTime {
+    fn from(time: SystemTime) -> Self {
+        let secs = match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(duration) => i64::try_from(duration.as_secs()).unwrap_or(i64::MAX),
+            Err(e) => -i64::try_from(e.duration().as_secs()).unwrap_or(i64::MAX),
+        };
+        Self(secs.clamp(Self::MIN.0, Self::NO_EXPIRY.0))
+    }
+}
+
+impl fmt::Display for CertTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day, hour, minute, second) = self.to_ymd_hms();
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year, month, day, hour, minute, second
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for CertTime {
+    fn from(time: time::OffsetDateTime) -> Self {
+        Self(time.unix_timestamp().clamp(Self::MIN.0, Self::NO_EXPIRY.0))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<CertTime> for time::OffsetDateTime {
+    type Error = PkiError;
+
+    fn try_from(time: CertTime) -> Result<Self> {
+        time::OffsetDateTime::from_unix_timestamp(time.0).map_err(|_| PkiError::InvalidParameters)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for CertTime {
+    fn from(time: chrono::DateTime<Tz>) -> Self {
+        Self(time.timestamp().clamp(Self::MIN.0, Self::NO_EXPIRY.0))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<CertTime> for chrono::DateTime<chrono::Utc> {
+    type Error = PkiError;
+
+    fn try_from(time: CertTime) -> Result<Self> {
+        chrono::DateTime::from_timestamp(time.0, 0).ok_or(PkiError::InvalidParameters)
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for the proleptic Gregorian calendar date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Proleptic Gregorian calendar date for the days since 1970-01-01
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day)
+}
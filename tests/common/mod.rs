@@ -0,0 +1,25 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use pki::{CertName, CertificateBuilder, KeyStore, PrivateKey, UsageProfile};
+
+/// Certificate builder with the common name, usage and a new EC key,
+/// self-signed unless the signer is given
+pub fn builder<'a, U: Into<UsageProfile>>(
+    signer: Option<&'a KeyStore>,
+    cn: &str,
+    usage: U,
+) -> CertificateBuilder<'a> {
+    let mut builder = CertificateBuilder::new();
+    builder
+        .subject(CertName::new([("CN", cn)]).unwrap())
+        .usage(usage)
+        .private_key(PrivateKey::new_ec(256).unwrap());
+    builder.signer(signer);
+    builder
+}
+
+/// Key store created with the [`builder`] fixture
+pub fn gen_store<U: Into<UsageProfile>>(signer: Option<&KeyStore>, cn: &str, usage: U) -> KeyStore {
+    builder(signer, cn, usage).build().unwrap()
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::builder;
+use pki::{CertUsage, CertificateVerifier, OID_BASIC_CONSTRAINTS};
+
+#[test]
+fn test_ca_basic_constraints() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    let ext = root.certs()[0].extension(OID_BASIC_CONSTRAINTS).unwrap();
+    assert!(ext.critical());
+    assert_eq!(ext.value(), [0x30, 0x03, 0x01, 0x01, 0xff]);
+
+    let intermediate = builder(Some(&root), "Intermediate CA", CertUsage::CA)
+        .path_len(0)
+        .basic_constraints_critical(false)
+        .build()
+        .unwrap();
+    let ext = intermediate.certs()[0]
+        .extension(OID_BASIC_CONSTRAINTS)
+        .unwrap();
+    assert!(!ext.critical());
+    assert_eq!(
+        ext.value(),
+        [0x30, 0x06, 0x01, 0x01, 0xff, 0x02, 0x01, 0x00]
+    );
+
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_root(&root.certs()[0]);
+    let leaf = builder(Some(&intermediate), "leaf", CertUsage::TlsServer)
+        .build()
+        .unwrap();
+    verifier.verify(leaf.certs()).unwrap();
+
+    let sub_ca = builder(Some(&intermediate), "Sub CA", CertUsage::CA)
+        .build()
+        .unwrap();
+    let leaf = builder(Some(&sub_ca), "leaf", CertUsage::TlsServer)
+        .build()
+        .unwrap();
+    assert!(verifier.verify(leaf.certs()).is_err());
+}
+
+#[test]
+fn test_end_entity_basic_constraints() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+
+    let leaf = builder(Some(&root), "leaf", CertUsage::TlsServer)
+        .path_len(3)
+        .build()
+        .unwrap();
+    let ext = leaf.certs()[0].extension(OID_BASIC_CONSTRAINTS).unwrap();
+    assert!(!ext.critical());
+    assert_eq!(ext.value(), [0x30, 0x00]);
+
+    let leaf = builder(Some(&root), "leaf", CertUsage::TlsServer)
+        .basic_constraints_critical(true)
+        .build()
+        .unwrap();
+    assert!(leaf.certs()[0]
+        .extension(OID_BASIC_CONSTRAINTS)
+        .unwrap()
+        .critical());
+
+    let leaf = builder(Some(&root), "leaf", CertUsage::TlsServer)
+        .end_entity_basic_constraints(false)
+        .build()
+        .unwrap();
+    assert!(leaf.certs()[0].extension(OID_BASIC_CONSTRAINTS).is_none());
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&root.certs()[0])
+        .verify(leaf.certs())
+        .unwrap();
+
+    let ca = builder(None, "Root CA", CertUsage::CA)
+        .end_entity_basic_constraints(false)
+        .build()
+        .unwrap();
+    assert!(ca.certs()[0].extension(OID_BASIC_CONSTRAINTS).is_some());
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use pki::{CertName, NameAttribute, NameFormat, NameStringType, PkiError};
+
+#[test]
+fn test_parse_rfc4514() {
+    let name = CertName::parse("CN=host,O=Acme\\, Inc,C=US").unwrap();
+    let entries: Vec<_> = name
+        .entries()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("C".to_owned(), "US".to_owned()),
+            ("O".to_owned(), "Acme, Inc".to_owned()),
+            ("CN".to_owned(), "host".to_owned())
+        ]
+    );
+    assert_eq!(name.to_string(), "CN=host,O=Acme\\, Inc,C=US");
+    assert_eq!(
+        name.display(NameFormat::OpenSsl).to_string(),
+        "/C=US/O=Acme, Inc/CN=host"
+    );
+}
+
+#[test]
+fn test_parse_escapes_and_hex() {
+    let name = CertName::parse("cn=\\#hash\\2b\\ ,OID.2.5.4.10=#0c0441636d65").unwrap();
+    assert_eq!(name.to_string(), "CN=\\#hash\\+\\ ,O=Acme");
+
+    let name = CertName::parse("CN=\\D0\\9F\\D1\\80\\D0\\B8\\D0\\B2\\D0\\B5\\D1\\82").unwrap();
+    assert_eq!(name.to_string(), "CN=Привет");
+
+    let name = CertName::parse("1.2.3.4=#1e020041").unwrap();
+    assert_eq!(name.to_string(), "1.2.3.4=A");
+
+    assert!(CertName::parse("CN").is_err());
+    assert!(matches!(
+        CertName::parse("CN="),
+        Err(PkiError::InvalidName(_))
+    ));
+    assert!(CertName::parse("CN=a\\").is_err());
+    assert!(CertName::parse("XYZ=a").is_err());
+    assert!(CertName::parse("CN=#0c05").is_err());
+    assert!(CertName::parse("CN=#04020102").is_err());
+}
+
+#[test]
+fn test_multi_valued_rdn() {
+    let name = CertName::parse("CN=host+UID=42,O=Acme").unwrap();
+    assert_eq!(name.to_string(), "CN=host+UID=42,O=Acme");
+    assert_eq!(
+        name.display(NameFormat::OpenSsl).to_string(),
+        "/O=Acme/CN=host+UID=42"
+    );
+
+    let parsed = CertName::parse("/O=Acme\\/Sub/CN=host+UID=42").unwrap();
+    assert_eq!(parsed.to_string(), "CN=host+UID=42,O=Acme/Sub");
+}
+
+#[test]
+fn test_name_builder() {
+    let name = CertName::builder()
+        .attribute(NameAttribute::new("C", "US").string_type(NameStringType::Printable))
+        .attribute(NameAttribute::new("2.5.4.10", "Acme").string_type(NameStringType::Utf8))
+        .multi_valued([
+            NameAttribute::new("CN", "host").string_type(NameStringType::Bmp),
+            NameAttribute::new("1.3.6.1.4.1.99999.1", "dev-42").string_type(NameStringType::Ia5),
+        ])
+        .build()
+        .unwrap();
+
+    let der = name.to_der().unwrap();
+    let contains = |needle: &[u8]| der.windows(needle.len()).any(|w| w == needle);
+    assert!(contains(&[0x13, 0x02, b'U', b'S']));
+    assert!(contains(&[0x0c, 0x04, b'A', b'c', b'm', b'e']));
+    assert!(contains(&[0x1e, 0x08, 0, b'h', 0, b'o', 0, b's', 0, b't']));
+    assert!(contains(&[0x16, 0x06, b'd', b'e', b'v', b'-', b'4', b'2']));
+
+    assert_eq!(
+        name.to_string(),
+        "CN=host+1.3.6.1.4.1.99999.1=dev-42,O=Acme,C=US"
+    );
+    assert_eq!(CertName::from_der(&der).unwrap().to_der().unwrap(), der);
+    assert!(name
+        .entries()
+        .any(|(k, v)| k == "1.3.6.1.4.1.99999.1" && v == "dev-42"));
+
+    assert!(CertName::builder()
+        .attribute(NameAttribute::new("C", "U_S").string_type(NameStringType::Printable))
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_name_comparison() {
+    let name = CertName::parse("CN=My  Host,O=Acme,C=US").unwrap();
+    let other = CertName::builder()
+        .attribute(NameAttribute::new("C", "us").string_type(NameStringType::Utf8))
+        .attribute(NameAttribute::new("O", " ACME ").string_type(NameStringType::Bmp))
+        .attribute(NameAttribute::new("CN", "my host").string_type(NameStringType::Printable))
+        .build()
+        .unwrap();
+    assert_eq!(name, other);
+    assert_ne!(name, CertName::parse("CN=My Host,O=Acme").unwrap());
+    assert_eq!(
+        CertName::parse("CN=a+UID=b").unwrap(),
+        CertName::parse("UID=B+CN=A").unwrap()
+    );
+
+    let mut map = HashMap::new();
+    map.insert(name, 1);
+    assert_eq!(map.get(&other), Some(&1));
+
+    let store = pki::util::create_easy_server_chain("localhost").unwrap();
+    let cert = store.certs()[0].clone();
+    let x509 = openssl::x509::X509::from_der(&cert.to_der().unwrap()).unwrap();
+    assert_eq!(cert.subject_name().openssl_hash(), x509.subject_name_hash());
+    assert_eq!(cert.issuer_name(), store.certs()[1].subject_name());
+
+    let attrs = CertName::parse("CN=a+UID=b,O=Acme").unwrap().attributes();
+    assert_eq!(attrs.len(), 3);
+    assert_eq!(attrs[0].oid(), "2.5.4.10");
+    assert_eq!(attrs[0].rdn_index(), 0);
+    assert_eq!(attrs[2].field(), "UID");
+    assert_eq!(attrs[2].rdn_index(), 1);
+    assert_eq!(attrs[2].text(), Some("b"));
+    assert_eq!(attrs[2].string_type(), Some(NameStringType::Utf8));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::builder;
+use pki::{CertUsage, Certificate, ChainComposition, KeyRollover, KeyStore};
+
+fn der(cert: &Certificate) -> Vec<u8> {
+    cert.to_der().unwrap()
+}
+
+#[test]
+fn test_chain_composition() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    let intermediate = builder(Some(&root), "Intermediate CA", CertUsage::CA)
+        .build()
+        .unwrap();
+    let issuing = builder(Some(&intermediate), "Issuing CA", CertUsage::CA)
+        .build()
+        .unwrap();
+
+    let full = builder(Some(&issuing), "leaf", CertUsage::TlsServer)
+        .build()
+        .unwrap();
+    assert_eq!(full.certs().len(), 4);
+
+    let without_root = builder(Some(&issuing), "leaf", CertUsage::TlsServer)
+        .chain_composition(ChainComposition::WithoutRoot)
+        .build()
+        .unwrap();
+    let certs: Vec<_> = without_root.certs()[1..].iter().map(der).collect();
+    assert_eq!(
+        certs,
+        [der(&issuing.certs()[0]), der(&intermediate.certs()[0])]
+    );
+
+    let leaf_only = builder(Some(&issuing), "leaf", CertUsage::TlsServer)
+        .chain_composition(ChainComposition::LeafOnly)
+        .build()
+        .unwrap();
+    assert_eq!(leaf_only.certs().len(), 1);
+    assert!(leaf_only.root().is_none());
+    assert!(leaf_only.intermediates().is_empty());
+}
+
+#[test]
+fn test_key_store_parts() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    assert_eq!(der(root.leaf().unwrap()), der(root.root().unwrap()));
+    assert!(root.intermediates().is_empty());
+
+    let intermediate = builder(Some(&root), "Intermediate CA", CertUsage::CA)
+        .build()
+        .unwrap();
+    let leaf = builder(Some(&intermediate), "leaf", CertUsage::TlsServer)
+        .build()
+        .unwrap();
+    assert!(!leaf.leaf().unwrap().is_self_signed());
+    assert_eq!(der(leaf.root().unwrap()), der(&root.certs()[0]));
+    let intermediates: Vec<_> = leaf.intermediates().into_iter().map(der).collect();
+    assert_eq!(intermediates, [der(&intermediate.certs()[0])]);
+
+    // the root is detected by signature rather than position or names
+    let store = KeyStore::new(
+        leaf.private_key().clone(),
+        [
+            leaf.certs()[0].clone(),
+            root.certs()[0].clone(),
+            intermediate.certs()[0].clone(),
+        ],
+    )
+    .unwrap();
+    assert_eq!(der(store.root().unwrap()), der(&root.certs()[0]));
+    assert_eq!(store.intermediates().len(), 1);
+
+    let new_root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    let rollover = KeyRollover::new(&root, &new_root).unwrap();
+    let cross = rollover.old_with_new();
+    assert!(!cross.leaf().unwrap().is_self_signed());
+    assert_eq!(der(cross.root().unwrap()), der(&new_root.certs()[0]));
+
+    let key_only = KeyStore::from_pkcs8(&leaf.private_key().to_pkcs8_pem().unwrap()).unwrap();
+    assert!(key_only.leaf().is_none());
+    assert!(key_only.intermediates().is_empty());
+    assert!(key_only.root().is_none());
+}
@@ -0,0 +1,111 @@
+use openssl::x509::X509;
+use pki::{
+    CertName, CertUsage, Certificate, CertificateBuilder, CertificateVerifier, Extension, PkiError,
+    PrivateKey, OID_BASIC_CONSTRAINTS, OID_KEY_USAGE,
+};
+
+const OPENSSL_CNF: &str = r#"
+# legacy CA profile
+[req]
+distinguished_name = req_dn
+
+[v3_req]
+basicConstraints = critical, CA:FALSE
+keyUsage = critical, digitalSignature, keyEncipherment
+extendedKeyUsage = serverAuth, clientAuth
+subjectKeyIdentifier = hash
+authorityKeyIdentifier = keyid:always
+subjectAltName = @alt_names
+1.3.6.1.4.1.99999.1 = ASN1:UTF8String:device-1
+
+[alt_names]
+DNS.1 = www.example.com
+DNS.2 = api.example.com
+IP.1 = 10.0.0.1
+"#;
+
+fn cert_text(cert: &Certificate) -> String {
+    let x509 = X509::from_der(&cert.to_der().unwrap()).unwrap();
+    String::from_utf8(x509.to_text().unwrap()).unwrap()
+}
+
+#[test]
+fn test_config_section() {
+    let ca = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "server")]).unwrap())
+        .signer(&ca)
+        .config_extensions(OPENSSL_CNF, Some("v3_req"))
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let cert = &store.certs()[0];
+
+    assert!(cert.extension(OID_BASIC_CONSTRAINTS).unwrap().critical());
+    assert!(cert.extension(OID_KEY_USAGE).unwrap().critical());
+    assert_eq!(
+        cert.extension("1.3.6.1.4.1.99999.1").unwrap().value(),
+        b"\x0c\x08device-1"
+    );
+    let text = cert_text(cert);
+    for expected in [
+        "TLS Web Server Authentication, TLS Web Client Authentication",
+        "DNS:www.example.com, DNS:api.example.com, IP Address:10.0.0.1",
+        "Digital Signature, Key Encipherment",
+    ] {
+        assert!(text.contains(expected), "missing {}", expected);
+    }
+    assert_eq!(cert.extensions().len(), 7);
+
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&ca.certs()[0])
+        .verify_for_host(store.certs(), "api.example.com")
+        .unwrap();
+}
+
+#[test]
+fn test_config_lines() {
+    let config = format!(
+        "basicConstraints = critical, CA:TRUE, pathlen:0\nkeyUsage = {}\n",
+        CertUsage::CA.usage()
+    );
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Intermediate CA")]).unwrap())
+        .usage(CertUsage::TlsServer)
+        .config_extensions(&config, None)
+        .extension(Extension::new(
+            OID_KEY_USAGE,
+            true,
+            [0x03, 0x02, 0x01, 0x06],
+        ))
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let cert = &store.certs()[0];
+    let text = cert_text(cert);
+    assert!(text.contains("CA:TRUE, pathlen:0"));
+    assert_eq!(
+        cert.extension(OID_KEY_USAGE).unwrap().value(),
+        [0x03, 0x02, 0x01, 0x06]
+    );
+
+    assert!(CertificateBuilder::new()
+        .config_extensions(OPENSSL_CNF, Some("missing"))
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .is_err());
+    assert!(matches!(
+        CertificateBuilder::new()
+            .config_extensions("unknownExtension = value", None)
+            .private_key(PrivateKey::new_ec(256).unwrap())
+            .build(),
+        Err(PkiError::InvalidExtension(name)) if name == "unknownExtension"
+    ));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{builder, gen_store};
+use pki::{
+    CertUsage, CertificateBuilder, CertificateVerifier, KeyRollover, KeyStore, PkiError,
+    PrivateKey, OID_SUBJECT_KEY_IDENTIFIER,
+};
+
+fn verifier(root: &KeyStore) -> CertificateVerifier {
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_root(&root.certs()[0]);
+    verifier
+}
+
+#[test]
+fn test_key_rollover() {
+    let old_ca = builder(None, "Old Root CA", CertUsage::CA)
+        .private_key(PrivateKey::new_rsa(2048).unwrap())
+        .build()
+        .unwrap();
+    let new_ca = gen_store(None, "New Root CA", CertUsage::CA);
+    let rollover = KeyRollover::new(&old_ca, &new_ca).unwrap();
+
+    let old_with_new = rollover.old_with_new();
+    let cert = &old_with_new.certs()[0];
+    assert_eq!(old_with_new.certs().len(), 2);
+    assert_eq!(
+        cert.subject_name().to_der().unwrap(),
+        old_ca.certs()[0].subject_name().to_der().unwrap()
+    );
+    assert_eq!(
+        cert.issuer_name().to_der().unwrap(),
+        new_ca.certs()[0].subject_name().to_der().unwrap()
+    );
+    assert_eq!(
+        cert.public_key().unwrap(),
+        old_ca.certs()[0].public_key().unwrap()
+    );
+    assert_eq!(
+        cert.extension(OID_SUBJECT_KEY_IDENTIFIER),
+        old_ca.certs()[0].extension(OID_SUBJECT_KEY_IDENTIFIER)
+    );
+
+    let new_with_old = rollover.new_with_old();
+    assert_eq!(
+        new_with_old.certs()[0].public_key().unwrap(),
+        new_ca.certs()[0].public_key().unwrap()
+    );
+    assert!(new_with_old.certs()[0].not_after().unwrap() <= old_ca.certs()[0].not_after().unwrap());
+
+    // old clients trust the old root only
+    let store = gen_store(Some(new_with_old), "leaf", CertUsage::TlsServer);
+    assert_eq!(store.certs().len(), 3);
+    verifier(&old_ca).verify(store.certs()).unwrap();
+    verifier(&new_ca)
+        .verify(gen_store(Some(&new_ca), "leaf", CertUsage::TlsServer).certs())
+        .unwrap();
+
+    // new clients can validate chains issued by the old CA
+    let store = gen_store(Some(&old_ca), "leaf", CertUsage::TlsServer);
+    let chain = [store.certs()[0].clone(), old_with_new.certs()[0].clone()];
+    verifier(&new_ca).verify(&chain).unwrap();
+    assert!(verifier(&new_ca).verify(store.certs()).is_err());
+
+    let key_only =
+        KeyStore::from_pkcs8(&PrivateKey::new_ec(256).unwrap().to_pkcs8_pem().unwrap()).unwrap();
+    assert!(matches!(
+        KeyRollover::new(&old_ca, &key_only),
+        Err(PkiError::InvalidParameters)
+    ));
+    assert!(matches!(
+        KeyRollover::new(&key_only, &new_ca),
+        Err(PkiError::InvalidParameters)
+    ));
+}
+
+#[test]
+fn test_cross_certificate() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA);
+    let partner = builder(None, "Partner Root CA", CertUsage::CA)
+        .private_key(PrivateKey::new_ec(384).unwrap())
+        .build()
+        .unwrap();
+
+    let original = &intermediate.certs()[0];
+    let mut builder = CertificateBuilder::cross_certificate(original).unwrap();
+    builder.signer(&partner);
+    assert!(matches!(builder.build(), Err(PkiError::MissingPrivateKey)));
+
+    let cross = builder.build_certificate().unwrap();
+    assert_eq!(cross.public_key().unwrap(), original.public_key().unwrap());
+    assert_eq!(cross.not_after().unwrap(), original.not_after().unwrap());
+    assert_eq!(cross.extensions().len(), original.extensions().len());
+
+    let store = gen_store(Some(&intermediate), "leaf", CertUsage::TlsServer);
+    let chain = [store.certs()[0].clone(), cross];
+    verifier(&partner).verify(&chain).unwrap();
+    verifier(&root).verify(store.certs()).unwrap();
+
+    let cross_store = builder
+        .private_key(intermediate.private_key().clone())
+        .issuer_chain([])
+        .build()
+        .unwrap();
+    assert_eq!(cross_store.certs().len(), 1);
+    let store = gen_store(Some(&cross_store), "leaf", CertUsage::TlsServer);
+    assert_eq!(store.certs().len(), 2);
+    verifier(&partner).verify(store.certs()).unwrap();
+
+    assert!(builder
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .is_err());
+}
@@ -0,0 +1,109 @@
+use pki::{
+    CertName, CertUsage, CertificateBuilder, CertificateVerifier, Extension, PrivateKey,
+    OID_AUTHORITY_KEY_IDENTIFIER, OID_BASIC_CONSTRAINTS, OID_EXTENDED_KEY_USAGE, OID_KEY_USAGE,
+    OID_SUBJECT_KEY_IDENTIFIER,
+};
+
+const DEVICE_OID: &str = "1.3.6.1.4.1.99999.42";
+
+#[test]
+fn test_default_extensions() {
+    let ca = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let ca_cert = &ca.certs()[0];
+    let oids: Vec<_> = ca_cert
+        .extensions()
+        .iter()
+        .map(|ext| ext.oid().to_owned())
+        .collect();
+    assert_eq!(
+        oids,
+        [
+            OID_BASIC_CONSTRAINTS,
+            OID_SUBJECT_KEY_IDENTIFIER,
+            OID_AUTHORITY_KEY_IDENTIFIER,
+            OID_KEY_USAGE
+        ]
+    );
+    assert!(ca_cert.extension(OID_BASIC_CONSTRAINTS).unwrap().critical());
+
+    let leaf = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "leaf")]).unwrap())
+        .signer(&ca)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let ski = ca_cert.extension(OID_SUBJECT_KEY_IDENTIFIER).unwrap();
+    let aki = leaf.certs()[0]
+        .extension(OID_AUTHORITY_KEY_IDENTIFIER)
+        .unwrap();
+    assert_eq!(&aki.value()[4..], &ski.value()[2..]);
+}
+
+#[test]
+fn test_custom_extensions() {
+    let ca = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+
+    let device_info = Extension::new(DEVICE_OID, false, b"\x0c\x08device-1".to_vec());
+    let key_usage = Extension::new(OID_KEY_USAGE, true, [0x03, 0x02, 0x07, 0x80]);
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "device")]).unwrap())
+        .signer(&ca)
+        .extension(device_info.clone())
+        .extension(key_usage.clone())
+        .remove_extension(OID_SUBJECT_KEY_IDENTIFIER)
+        .remove_extension(OID_EXTENDED_KEY_USAGE)
+        .remove_extension(DEVICE_OID)
+        .extension(device_info.clone())
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+
+    let extensions = store.certs()[0].extensions();
+    let oids: Vec<_> = extensions.iter().map(|ext| ext.oid()).collect();
+    assert_eq!(
+        oids,
+        [
+            OID_BASIC_CONSTRAINTS,
+            OID_AUTHORITY_KEY_IDENTIFIER,
+            OID_KEY_USAGE,
+            DEVICE_OID
+        ]
+    );
+    assert_eq!(extensions[2], key_usage);
+    assert_eq!(extensions[3], device_info);
+
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&ca.certs()[0])
+        .verify(store.certs())
+        .unwrap();
+
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "minimal")]).unwrap())
+        .signer(&ca)
+        .remove_extension(OID_BASIC_CONSTRAINTS)
+        .remove_extension(OID_SUBJECT_KEY_IDENTIFIER)
+        .remove_extension(OID_AUTHORITY_KEY_IDENTIFIER)
+        .remove_extension(OID_EXTENDED_KEY_USAGE)
+        .remove_extension(OID_KEY_USAGE)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    assert!(store.certs()[0].extensions().is_empty());
+
+    assert!(CertificateBuilder::new()
+        .extension(Extension::new("not an oid", false, [0x05, 0x00]))
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .is_err());
+}
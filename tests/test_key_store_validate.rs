@@ -0,0 +1,142 @@
+mod common;
+
+use common::builder;
+use pki::{
+    CertUsage, ChainComposition, Extension, KeyStore, KeyStoreMismatch, PkiError, PrivateKey,
+    OID_SUBJECT_KEY_IDENTIFIER,
+};
+
+fn key_id(id: u8) -> Extension {
+    let mut value = vec![0x04, 0x14];
+    value.extend([id; 20]);
+    Extension::new(OID_SUBJECT_KEY_IDENTIFIER, false, value)
+}
+
+fn assert_mismatch(store: &KeyStore, depth: usize, subject: &str, reason: KeyStoreMismatch) {
+    match store.validate() {
+        Err(PkiError::InvalidKeyStore {
+            depth: error_depth,
+            subject: error_subject,
+            reason: error_reason,
+        }) => {
+            assert_eq!(error_depth, depth);
+            assert!(error_subject.contains(subject), "{}", error_subject);
+            assert_eq!(error_reason, reason);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_validate_chain() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    root.validate().unwrap();
+    let intermediate = builder(Some(&root), "Intermediate CA", CertUsage::CA)
+        .build()
+        .unwrap();
+    let leaf = builder(Some(&intermediate), "leaf", CertUsage::TlsServer)
+        .build()
+        .unwrap();
+    leaf.validate().unwrap();
+
+    KeyStore::from_pkcs8(&leaf.to_pkcs8().unwrap())
+        .unwrap()
+        .validate()
+        .unwrap();
+    KeyStore::from_pkcs12(&leaf.to_pkcs12("leaf", "secret").unwrap(), "secret")
+        .unwrap()
+        .validate()
+        .unwrap();
+
+    let without_root = builder(Some(&intermediate), "leaf", CertUsage::TlsServer)
+        .chain_composition(ChainComposition::WithoutRoot)
+        .build()
+        .unwrap();
+    without_root.validate().unwrap();
+
+    let wrong_key = KeyStore::new(PrivateKey::new_ec(256).unwrap(), leaf.certs().to_vec()).unwrap();
+    assert_mismatch(&wrong_key, 0, "leaf", KeyStoreMismatch::PrivateKey);
+
+    let wrong_order = KeyStore::new(
+        leaf.private_key().clone(),
+        [
+            leaf.certs()[0].clone(),
+            root.certs()[0].clone(),
+            intermediate.certs()[0].clone(),
+        ],
+    )
+    .unwrap();
+    assert_mismatch(&wrong_order, 0, "leaf", KeyStoreMismatch::IssuerName);
+
+    let other_root = builder(None, "Other Root CA", CertUsage::CA)
+        .build()
+        .unwrap();
+    let wrong_root = KeyStore::new(
+        leaf.private_key().clone(),
+        [
+            leaf.certs()[0].clone(),
+            intermediate.certs()[0].clone(),
+            other_root.certs()[0].clone(),
+        ],
+    )
+    .unwrap();
+    assert_mismatch(
+        &wrong_root,
+        1,
+        "Intermediate CA",
+        KeyStoreMismatch::IssuerName,
+    );
+}
+
+#[test]
+fn test_validate_key_identifier_and_signature() {
+    let root = builder(None, "Root CA", CertUsage::CA)
+        .extension(key_id(1))
+        .build()
+        .unwrap();
+    let leaf = builder(Some(&root), "leaf", CertUsage::TlsServer)
+        .build()
+        .unwrap();
+    leaf.validate().unwrap();
+
+    // same name and key, different key identifier
+    let rekeyed_id = builder(None, "Root CA", CertUsage::CA)
+        .private_key(root.private_key().clone())
+        .extension(key_id(2))
+        .build()
+        .unwrap();
+    let store = KeyStore::new(
+        leaf.private_key().clone(),
+        [leaf.certs()[0].clone(), rekeyed_id.certs()[0].clone()],
+    )
+    .unwrap();
+    assert_mismatch(&store, 0, "leaf", KeyStoreMismatch::KeyIdentifier);
+
+    // same name and key identifier, different key
+    let impostor = builder(None, "Root CA", CertUsage::CA)
+        .extension(key_id(1))
+        .build()
+        .unwrap();
+    let store = KeyStore::new(
+        leaf.private_key().clone(),
+        [leaf.certs()[0].clone(), impostor.certs()[0].clone()],
+    )
+    .unwrap();
+    assert_mismatch(&store, 0, "leaf", KeyStoreMismatch::Signature);
+
+    // the last certificate with equal issuer and subject names is checked against its own key
+    let same_name = builder(Some(&root), "Root CA", CertUsage::CA)
+        .extension(key_id(1))
+        .build()
+        .unwrap();
+    let store = KeyStore::new(
+        same_name.private_key().clone(),
+        [same_name.certs()[0].clone()],
+    )
+    .unwrap();
+    assert_mismatch(&store, 0, "Root CA", KeyStoreMismatch::Signature);
+    same_name.validate().unwrap();
+
+    let error = store.validate().unwrap_err().to_string();
+    assert!(error.contains("depth 0"), "{}", error);
+}
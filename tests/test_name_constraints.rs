@@ -0,0 +1,90 @@
+use pki::{
+    CertName, CertUsage, CertificateBuilder, CertificateVerifier, KeyStore, NameConstraint,
+    PkiError, PrivateKey,
+};
+
+fn gen_constrained_ca() -> KeyStore {
+    CertificateBuilder::new()
+        .subject(CertName::new([("O", "Acme"), ("CN", "Constrained CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .permitted_names([
+            NameConstraint::dns("example.com").unwrap(),
+            NameConstraint::ip_network("10.0.0.0/8").unwrap(),
+            NameConstraint::ip_network("fd00::/8").unwrap(),
+            NameConstraint::email("example.com").unwrap(),
+            NameConstraint::directory_name(&CertName::new([("O", "Acme")]).unwrap()).unwrap(),
+        ])
+        .excluded_names([
+            NameConstraint::dns("bad.example.com").unwrap(),
+            NameConstraint::ip_network("10.1.0.0/16").unwrap(),
+        ])
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap()
+}
+
+fn verify_leaf(ca: &KeyStore, org: &str, alt_names: &[&str]) -> pki::Result<()> {
+    let leaf = CertificateBuilder::new()
+        .subject(CertName::new([("O", org), ("CN", "leaf")]).unwrap())
+        .signer(ca)
+        .alt_names(alt_names)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(ca.certs().last().unwrap())
+        .verify(leaf.certs())
+}
+
+#[test]
+fn test_name_constraints() {
+    let ca = gen_constrained_ca();
+    verify_leaf(
+        &ca,
+        "Acme",
+        &["www.example.com", "10.2.3.4", "fd00::1", "user@example.com"],
+    )
+    .unwrap();
+
+    for alt_name in [
+        "www.example.org",
+        "bad.example.com",
+        "www.bad.example.com",
+        "10.1.2.3",
+        "192.168.1.1",
+        "fe80::1",
+        "user@example.org",
+    ] {
+        assert!(
+            matches!(
+                verify_leaf(&ca, "Acme", &[alt_name]),
+                Err(PkiError::Verify(_))
+            ),
+            "{} must be rejected",
+            alt_name
+        );
+    }
+    assert!(verify_leaf(&ca, "Other", &["www.example.com"]).is_err());
+}
+
+#[test]
+fn test_invalid_name_constraints() {
+    assert!(NameConstraint::ip_network("10.0.0.0").is_err());
+    assert!(NameConstraint::ip_network("10.0.0.0/33").is_err());
+    assert!(NameConstraint::ip_network("fd00::/129").is_err());
+    assert!(NameConstraint::dns("пример.рф").is_err());
+    // the address must be the network address of the prefix
+    assert!(matches!(
+        NameConstraint::ip_network("10.1.2.3/8"),
+        Err(PkiError::InvalidParameters)
+    ));
+    assert!(NameConstraint::ip_network("10.0.0.1/31").is_err());
+    assert!(NameConstraint::ip_network("fd00::1/8").is_err());
+    assert!(NameConstraint::ip_network("10.0.0.1/32").is_ok());
+    assert!(NameConstraint::ip_network("0.0.0.0/0").is_ok());
+    assert_eq!(
+        NameConstraint::ip_network("10.0.0.0/8").unwrap(),
+        NameConstraint::ip("10.0.0.0".parse().unwrap(), 8).unwrap()
+    );
+}
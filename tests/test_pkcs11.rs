@@ -0,0 +1,139 @@
+//! PKCS#11 tests, they require an initialized token, for example with SoftHSMv2:
+//!
+//! ```text
+//! softhsm2-util --init-token --free --label pki-test --so-pin 1234 --pin 1234
+//! PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so PKCS11_SLOT=<slot> PKCS11_PIN=1234 \
+//!     cargo test --features pkcs11 --test test_pkcs11 -- --ignored
+//! ```
+//!
+//! The `pkcs11` workflow in `.github/workflows` runs them against SoftHSMv2.
+#![cfg(feature = "pkcs11")]
+
+use openssl::{nid::Nid, x509::X509};
+use pki::{
+    CertName, CertUsage, CertificateBuilder, CertificateRequestBuilder, CertificateVerifier,
+    KeyStore, Pkcs11Config, PkiError, PrivateKey, PrivateKeyType, SerialNumber,
+};
+
+fn config(label: &str) -> Pkcs11Config {
+    let module = std::env::var("PKCS11_MODULE").expect("PKCS11_MODULE is not set");
+    let slot = std::env::var("PKCS11_SLOT")
+        .expect("PKCS11_SLOT is not set")
+        .parse()
+        .unwrap();
+    let pin = std::env::var("PKCS11_PIN").expect("PKCS11_PIN is not set");
+    // unique label for every run on the same token
+    let label = format!("{}-{}", label, SerialNumber::random().unwrap());
+    Pkcs11Config::new(module, slot, &label).pin(&pin)
+}
+
+#[test]
+#[ignore]
+fn test_pkcs11_ca() {
+    let root_config = config("root");
+    let root_key = PrivateKey::new_pkcs11_rsa(&root_config, 2048).unwrap();
+    assert_eq!(root_key.key_type(), PrivateKeyType::Rsa);
+    assert_eq!(root_key.bits(), 2048);
+    assert!(!root_key.is_exportable());
+
+    let root_cert = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "HSM Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .external_signer(&root_key)
+        .build_certificate()
+        .unwrap();
+    assert!(root_cert.is_self_signed());
+    let root = KeyStore::new(root_key, [root_cert.clone()]).unwrap();
+    root.validate().unwrap();
+
+    let intermediate_key = PrivateKey::new_pkcs11_ec(&config("intermediate"), 256).unwrap();
+    assert_eq!(intermediate_key.key_type(), PrivateKeyType::Ec);
+    let request = CertificateRequestBuilder::new()
+        .subject(CertName::new([("CN", "HSM Intermediate CA")]).unwrap())
+        .signer(&intermediate_key)
+        .build()
+        .unwrap();
+    assert!(request.verify().unwrap());
+
+    let intermediate_cert = CertificateBuilder::from_request(&request)
+        .unwrap()
+        .usage(CertUsage::CA)
+        .signer(&root)
+        .build_certificate()
+        .unwrap();
+    let intermediate =
+        KeyStore::new(intermediate_key, [intermediate_cert, root_cert.clone()]).unwrap();
+    intermediate.validate().unwrap();
+
+    let leaf = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "leaf")]).unwrap())
+        .alt_names(["leaf.example.com"])
+        .signer(&intermediate)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(leaf.certs().len(), 3);
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&root_cert)
+        .verify_for_host(leaf.certs(), "leaf.example.com")
+        .unwrap();
+
+    // the key is found by label in a new session
+    let reopened = PrivateKey::from_pkcs11(&root_config).unwrap();
+    assert_eq!(
+        reopened.public_key().unwrap(),
+        root_cert.public_key().unwrap()
+    );
+    assert!(matches!(reopened.to_der(), Err(PkiError::NonExportableKey)));
+    assert!(matches!(
+        root.to_pkcs12("root", "password"),
+        Err(PkiError::NonExportableKey)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_pkcs11_ec_384() {
+    let key = PrivateKey::new_pkcs11_ec(&config("p384"), 384).unwrap();
+    assert_eq!(key.key_type(), PrivateKeyType::Ec);
+    assert_eq!(key.bits(), 384);
+
+    let cert = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "HSM P-384 CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .external_signer(&key)
+        .build_certificate()
+        .unwrap();
+    assert!(cert.is_self_signed());
+    let x509 = X509::from_der(&cert.to_der().unwrap()).unwrap();
+    assert_eq!(
+        x509.signature_algorithm().object().nid(),
+        Nid::ECDSA_WITH_SHA384
+    );
+}
+
+#[test]
+#[ignore]
+fn test_pkcs11_duplicate_label() {
+    let config = config("duplicate");
+    PrivateKey::new_pkcs11_ec(&config, 256).unwrap();
+    assert!(matches!(
+        PrivateKey::new_pkcs11_ec(&config, 256),
+        Err(PkiError::DuplicateLabel(label)) if label == config.label()
+    ));
+    assert!(matches!(
+        PrivateKey::new_pkcs11_rsa(&config, 2048),
+        Err(PkiError::DuplicateLabel(_))
+    ));
+    PrivateKey::from_pkcs11(&config).unwrap();
+}
+
+#[test]
+#[ignore]
+fn test_pkcs11_missing_key() {
+    assert!(matches!(
+        PrivateKey::from_pkcs11(&config("missing")),
+        Err(PkiError::MissingPrivateKey)
+    ));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::builder;
+use pki::{CertUsage, CertificatePolicy, CertificateVerifier, KeyStore};
+
+const POLICY_1: &str = "1.3.6.1.4.1.99999.1";
+const POLICY_2: &str = "1.3.6.1.4.1.99999.2";
+const POLICY_3: &str = "1.3.6.1.4.1.99999.3";
+
+fn verifier(root: &KeyStore) -> CertificateVerifier {
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_root(&root.certs()[0]);
+    verifier
+}
+
+fn leaf(signer: &KeyStore, policies: &[&str]) -> KeyStore {
+    builder(Some(signer), "leaf", CertUsage::TlsServer)
+        .policies(policies.iter().map(CertificatePolicy::new))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_certificate_policies() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    let intermediate = builder(Some(&root), "Intermediate CA", CertUsage::CA)
+        .policies([
+            CertificatePolicy::new(POLICY_1)
+                .cps_uri("https://example.com/cps")
+                .user_notice("Test policy"),
+            CertificatePolicy::new(POLICY_2),
+        ])
+        .build()
+        .unwrap();
+
+    let mut verifier = verifier(&root);
+    verifier
+        .acceptable_policies([POLICY_1])
+        .explicit_policy(true);
+    let report = verifier
+        .verify_detailed(leaf(&intermediate, &[POLICY_1]).certs())
+        .unwrap();
+    assert!(report.is_valid());
+    let tree = report.policy_tree().unwrap();
+    assert_eq!(tree.user_policies(), [POLICY_1]);
+    assert_eq!(tree.levels().len(), 3);
+    assert!(tree.levels()[2]
+        .iter()
+        .any(|node| node.policy() == POLICY_1 && node.parent() == Some(POLICY_1)));
+
+    assert!(verifier
+        .verify(leaf(&intermediate, &[POLICY_2]).certs())
+        .is_err());
+    assert!(verifier.verify(leaf(&intermediate, &[]).certs()).is_err());
+
+    let mut verifier = self::verifier(&root);
+    verifier.verify(leaf(&intermediate, &[]).certs()).unwrap();
+    verifier.acceptable_policies([POLICY_3]);
+    verifier
+        .verify(leaf(&intermediate, &[POLICY_1]).certs())
+        .unwrap();
+    verifier.explicit_policy(true);
+    assert!(verifier
+        .verify(leaf(&intermediate, &[POLICY_1]).certs())
+        .is_err());
+}
+
+#[test]
+fn test_policy_mappings_and_constraints() {
+    let root = builder(None, "Root CA", CertUsage::CA).build().unwrap();
+    let mapping = builder(Some(&root), "Mapping CA", CertUsage::CA)
+        .policies([CertificatePolicy::new(POLICY_1)])
+        .policy_mappings([(POLICY_1, POLICY_3)])
+        .build()
+        .unwrap();
+
+    let mut verifier = verifier(&root);
+    verifier
+        .acceptable_policies([POLICY_1])
+        .explicit_policy(true);
+    verifier
+        .verify(leaf(&mapping, &[POLICY_3]).certs())
+        .unwrap();
+    verifier.inhibit_policy_mapping(true);
+    assert!(verifier
+        .verify(leaf(&mapping, &[POLICY_3]).certs())
+        .is_err());
+
+    let constrained = builder(Some(&root), "Constrained CA", CertUsage::CA)
+        .policies([CertificatePolicy::any()])
+        .policy_constraints(Some(0), None)
+        .build()
+        .unwrap();
+    let mut verifier = self::verifier(&root);
+    verifier.acceptable_policies([CertificatePolicy::any().oid()]);
+    verifier
+        .verify(leaf(&constrained, &[POLICY_2]).certs())
+        .unwrap();
+    assert!(verifier.verify(leaf(&constrained, &[]).certs()).is_err());
+
+    let inhibited = builder(Some(&root), "Inhibited CA", CertUsage::CA)
+        .policies([CertificatePolicy::any()])
+        .inhibit_any_policy(0)
+        .build()
+        .unwrap();
+    let any = CertificatePolicy::any();
+    let mut verifier = self::verifier(&root);
+    verifier
+        .acceptable_policies([POLICY_1])
+        .explicit_policy(true);
+    verifier
+        .verify(leaf(&constrained, &[any.oid()]).certs())
+        .unwrap();
+    assert!(verifier
+        .verify(leaf(&inhibited, &[any.oid()]).certs())
+        .is_err());
+}
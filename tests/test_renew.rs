@@ -0,0 +1,144 @@
+mod common;
+
+use common::gen_store;
+use openssl::x509::X509;
+use pki::{
+    CertName, CertTime, CertUsage, Certificate, CertificateBuilder, CertificatePolicy,
+    CertificateVerifier, Extension, KeyStore, PkiError, PrivateKey, RevocationInfo, UsageProfile,
+    OID_AUTHORITY_INFO_ACCESS, OID_AUTHORITY_KEY_IDENTIFIER, OID_BASIC_CONSTRAINTS,
+    OID_CERTIFICATE_POLICIES, OID_CRL_DISTRIBUTION_POINTS, OID_EXTENDED_KEY_USAGE, OID_KEY_USAGE,
+    OID_SUBJECT_ALT_NAME, OID_SUBJECT_KEY_IDENTIFIER,
+};
+
+const DEVICE_OID: &str = "1.3.6.1.4.1.99999.42";
+
+fn public_key(cert: &Certificate) -> Vec<u8> {
+    X509::from_der(&cert.to_der().unwrap())
+        .unwrap()
+        .public_key()
+        .unwrap()
+        .public_key_to_der()
+        .unwrap()
+}
+
+fn assert_same_extension(old: &Certificate, new: &Certificate, oid: &str) {
+    assert_eq!(old.extension(oid), new.extension(oid), "{}", oid);
+}
+
+#[test]
+fn test_renew_with_same_key() {
+    let mut ca = gen_store(None, "Root CA", CertUsage::CA);
+    ca.set_revocation_info(RevocationInfo::new().ocsp_responder("http://ocsp.example.com"));
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "device"), ("O", "Example")]).unwrap())
+        .signer(&ca)
+        .usage(UsageProfile::time_stamping())
+        .alt_names(["device.example.com", "10.0.0.1", "admin@example.com"])
+        .policies([CertificatePolicy::new("1.3.6.1.4.1.99999.1")])
+        .extension(Extension::new(DEVICE_OID, false, b"\x0c\x01x".to_vec()))
+        .not_before(CertTime::from_ymd_hms(2020, 1, 1, 0, 0, 0).unwrap())
+        .not_after(CertTime::from_ymd_hms(2020, 1, 31, 0, 0, 0).unwrap())
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let old = &store.certs()[0];
+
+    let renewed = CertificateBuilder::from_key_store(&store)
+        .unwrap()
+        .signer(&ca)
+        .build()
+        .unwrap();
+    let new = &renewed.certs()[0];
+
+    assert_eq!(
+        new.subject_name().to_der().unwrap(),
+        old.subject_name().to_der().unwrap()
+    );
+    assert_eq!(new.extensions().len(), old.extensions().len());
+    for oid in [
+        OID_BASIC_CONSTRAINTS,
+        OID_KEY_USAGE,
+        OID_EXTENDED_KEY_USAGE,
+        OID_SUBJECT_ALT_NAME,
+        OID_CERTIFICATE_POLICIES,
+        OID_SUBJECT_KEY_IDENTIFIER,
+        OID_AUTHORITY_KEY_IDENTIFIER,
+        DEVICE_OID,
+    ] {
+        assert_same_extension(old, new, oid);
+    }
+    assert_ne!(new.serial_number().unwrap(), old.serial_number().unwrap());
+    assert_eq!(public_key(new), public_key(old));
+    let lifetime =
+        new.not_after().unwrap().unix_timestamp() - new.not_before().unwrap().unix_timestamp();
+    assert_eq!(lifetime, 30 * 24 * 60 * 60);
+    assert!(new.not_before().unwrap() > old.not_after().unwrap());
+
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&ca.certs()[0])
+        .verify(renewed.certs())
+        .unwrap();
+}
+
+#[test]
+fn test_rekey_with_new_signer() {
+    let mut old_ca = gen_store(None, "Old Root CA", CertUsage::CA);
+    old_ca.set_revocation_info(RevocationInfo::new().ocsp_responder("http://old.example.com"));
+    let new_ca = gen_store(None, "New Root CA", CertUsage::CA);
+
+    let intermediate = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Intermediate CA")]).unwrap())
+        .signer(&old_ca)
+        .usage(CertUsage::CA)
+        .path_len(0)
+        .not_after(CertTime::NO_EXPIRY)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let old = &intermediate.certs()[0];
+    assert!(old.extension(OID_AUTHORITY_INFO_ACCESS).is_some());
+
+    let rekeyed = CertificateBuilder::from_certificate(old)
+        .unwrap()
+        .signer(&new_ca)
+        .private_key(PrivateKey::new_ec(384).unwrap())
+        .build()
+        .unwrap();
+    let new = &rekeyed.certs()[0];
+
+    assert_same_extension(old, new, OID_BASIC_CONSTRAINTS);
+    assert_same_extension(old, new, OID_KEY_USAGE);
+    assert!(new.extension(OID_EXTENDED_KEY_USAGE).is_none());
+    assert!(new.extension(OID_AUTHORITY_INFO_ACCESS).is_none());
+    assert!(new.extension(OID_CRL_DISTRIBUTION_POINTS).is_none());
+    assert_ne!(public_key(new), public_key(old));
+    assert_ne!(
+        new.extension(OID_SUBJECT_KEY_IDENTIFIER),
+        old.extension(OID_SUBJECT_KEY_IDENTIFIER)
+    );
+    assert!(new.not_after().unwrap().is_no_expiry());
+
+    let leaf = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "leaf")]).unwrap())
+        .signer(&rekeyed)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&new_ca.certs()[0])
+        .verify(leaf.certs())
+        .unwrap();
+}
+
+#[test]
+fn test_renew_without_certificate() {
+    let key = PrivateKey::new_ec(256).unwrap();
+    let store = KeyStore::from_pkcs8(&key.to_pkcs8_pem().unwrap()).unwrap();
+    assert!(store.certs().is_empty());
+    assert!(matches!(
+        CertificateBuilder::from_key_store(&store),
+        Err(PkiError::InvalidParameters)
+    ));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::gen_store;
+use pki::{
+    CertName, CertUsage, CertificateBuilder, CertificateRequest, CertificateRequestBuilder,
+    CertificateVerifier, Extension, PkiError, PrivateKey, OID_SUBJECT_ALT_NAME,
+};
+
+#[test]
+fn test_request() {
+    let key = PrivateKey::new_rsa(2048).unwrap();
+    let request = CertificateRequestBuilder::new()
+        .subject(CertName::new([("CN", "device"), ("O", "Example")]).unwrap())
+        .alt_names(["device.example.com", "10.0.0.1"])
+        .extension(Extension::new(
+            "1.3.6.1.4.1.99999.42",
+            false,
+            b"\x05\x00".to_vec(),
+        ))
+        .signer(&key)
+        .build()
+        .unwrap();
+    assert!(request.verify().unwrap());
+    assert_eq!(request.public_key().unwrap(), key.public_key().unwrap());
+    assert_eq!(request.extensions().len(), 2);
+
+    let parsed = CertificateRequest::from_pem(&request.to_pem().unwrap()).unwrap();
+    assert_eq!(parsed.to_der().unwrap(), request.to_der().unwrap());
+    assert_eq!(
+        parsed.subject_name().to_string(),
+        request.subject_name().to_string()
+    );
+
+    let ca = gen_store(None, "Root CA", CertUsage::CA);
+    let cert = CertificateBuilder::from_request(&parsed)
+        .unwrap()
+        .signer(&ca)
+        .build_certificate()
+        .unwrap();
+    assert_eq!(cert.public_key().unwrap(), key.public_key().unwrap());
+    assert_eq!(
+        cert.extension(OID_SUBJECT_ALT_NAME),
+        request.extension(OID_SUBJECT_ALT_NAME)
+    );
+    assert!(cert.extension("1.3.6.1.4.1.99999.42").is_none());
+
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(&ca.certs()[0])
+        .verify_for_host(&[cert, ca.certs()[0].clone()], "device.example.com")
+        .unwrap();
+}
+
+#[test]
+fn test_request_from_key_store() {
+    let ca = gen_store(None, "Root CA", CertUsage::CA);
+    let request = CertificateRequestBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .signer(&ca)
+        .build()
+        .unwrap();
+    assert!(request.verify().unwrap());
+    assert_eq!(
+        request.public_key().unwrap(),
+        ca.private_key().public_key().unwrap()
+    );
+    assert!(request.extensions().is_empty());
+
+    assert!(matches!(
+        CertificateRequestBuilder::new().build(),
+        Err(PkiError::MissingPrivateKey)
+    ));
+}
+
+#[test]
+fn test_request_tampered() {
+    let key = PrivateKey::new_ec(256).unwrap();
+    let request = CertificateRequestBuilder::new()
+        .subject(CertName::new([("CN", "device")]).unwrap())
+        .signer(&key)
+        .build()
+        .unwrap();
+    let mut der = request.to_der().unwrap();
+    let pos = der.windows(6).position(|w| w == b"device").unwrap();
+    der[pos] = b'D';
+
+    let tampered = CertificateRequest::from_der(&der).unwrap();
+    assert!(!tampered.verify().unwrap());
+    assert!(CertificateBuilder::from_request(&tampered).is_err());
+}
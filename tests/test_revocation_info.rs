@@ -0,0 +1,73 @@
+use openssl::x509::X509;
+use pki::{
+    CertName, CertUsage, Certificate, CertificateBuilder, DistributionPoint, PrivateKey,
+    RevocationInfo, RevocationReason,
+};
+
+fn cert_text(cert: &Certificate) -> String {
+    let x509 = X509::from_der(&cert.to_der().unwrap()).unwrap();
+    String::from_utf8(x509.to_text().unwrap()).unwrap()
+}
+
+#[test]
+fn test_revocation_info() {
+    let mut ca = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    assert!(ca.revocation_info().is_none());
+
+    ca.set_revocation_info(
+        RevocationInfo::new()
+            .crl_distribution_point(
+                DistributionPoint::new("http://crl.example.com/root.crl")
+                    .uri("ldap://ldap.example.com/cn=Root%20CA")
+                    .reasons([
+                        RevocationReason::KeyCompromise,
+                        RevocationReason::AaCompromise,
+                    ])
+                    .crl_issuer(&CertName::new([("CN", "CRL Issuer")]).unwrap())
+                    .unwrap(),
+            )
+            .freshest_crl(DistributionPoint::new("http://crl.example.com/delta.crl"))
+            .ocsp_responder("http://ocsp.example.com")
+            .ca_issuers("http://example.com/root.crt"),
+    );
+
+    let leaf = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "leaf")]).unwrap())
+        .signer(&ca)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let text = cert_text(&leaf.certs()[0]);
+    for expected in [
+        "X509v3 CRL Distribution Points",
+        "URI:http://crl.example.com/root.crl",
+        "URI:ldap://ldap.example.com/cn=Root%20CA",
+        "Key Compromise, AA Compromise",
+        "CRL Issuer:",
+        "DirName:CN = CRL Issuer",
+        "X509v3 Freshest CRL",
+        "URI:http://crl.example.com/delta.crl",
+        "OCSP - URI:http://ocsp.example.com",
+        "CA Issuers - URI:http://example.com/root.crt",
+    ] {
+        assert!(text.contains(expected), "missing {}", expected);
+    }
+
+    let overridden = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "other")]).unwrap())
+        .signer(&ca)
+        .revocation_info(RevocationInfo::new().ocsp_responder("http://other.example.com"))
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let text = cert_text(&overridden.certs()[0]);
+    assert!(text.contains("OCSP - URI:http://other.example.com"));
+    assert!(!text.contains("CRL Distribution Points"));
+
+    assert!(!cert_text(&ca.certs()[0]).contains("CRL Distribution Points"));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::{cell::Cell, collections::HashSet};
+
+use common::builder;
+use pki::{CertUsage, PkiError, Result, SerialAllocator, SerialNumber, MAX_SERIAL_NUMBER_LENGTH};
+
+struct CounterAllocator(Cell<u64>);
+
+impl SerialAllocator for CounterAllocator {
+    fn allocate(&self) -> Result<SerialNumber> {
+        let next = self.0.get() + 1;
+        self.0.set(next);
+        next.try_into()
+    }
+}
+
+#[test]
+fn test_serial_number() {
+    let bytes = [0x7f; MAX_SERIAL_NUMBER_LENGTH];
+    let serial = SerialNumber::from_bytes(&bytes).unwrap();
+    let store = builder(None, "test", CertUsage::TlsServer)
+        .serial(serial.clone())
+        .build()
+        .unwrap();
+    assert_eq!(store.certs()[0].serial_number().unwrap(), serial);
+    assert_eq!(serial.to_string(), "7F".repeat(MAX_SERIAL_NUMBER_LENGTH));
+
+    let serial = SerialNumber::from_hex("00ff00").unwrap();
+    assert_eq!(serial.as_bytes(), [0xff, 0x00]);
+    assert_eq!(serial, SerialNumber::try_from(0xff00u64).unwrap());
+
+    assert!(SerialNumber::from_bytes(&[0x01; MAX_SERIAL_NUMBER_LENGTH + 1]).is_err());
+    assert!(SerialNumber::from_bytes(&[0x80; MAX_SERIAL_NUMBER_LENGTH]).is_err());
+    assert!(SerialNumber::from_bytes(&[0x00, 0x00]).is_err());
+    assert!(SerialNumber::from_hex("-01").is_err());
+    assert!(SerialNumber::try_from(0u64).is_err());
+    assert!(SerialNumber::try_from(0u128).is_err());
+    assert!(matches!(
+        builder(None, "test", CertUsage::TlsServer)
+            .serial_number(0)
+            .build(),
+        Err(PkiError::InvalidParameters)
+    ));
+
+    let store = builder(None, "test", CertUsage::TlsServer)
+        .serial_number(12345)
+        .build()
+        .unwrap();
+    assert_eq!(
+        store.certs()[0].serial_number().unwrap(),
+        SerialNumber::try_from(12345u64).unwrap()
+    );
+}
+
+#[test]
+fn test_serial_allocator() {
+    let serials = (0..16)
+        .map(|_| {
+            let store = builder(None, "test", CertUsage::TlsServer).build().unwrap();
+            let serial = store.certs()[0].serial_number().unwrap();
+            assert!(serial.as_bytes().len() * 8 >= 64);
+            serial
+        })
+        .collect::<HashSet<_>>();
+    assert_eq!(serials.len(), 16);
+
+    let allocator = CounterAllocator(Cell::new(0));
+    for expected in 1..=3u64 {
+        let store = builder(None, "test", CertUsage::TlsServer)
+            .serial_allocator(&allocator)
+            .build()
+            .unwrap();
+        assert_eq!(
+            store.certs()[0].serial_number().unwrap(),
+            SerialNumber::try_from(expected).unwrap()
+        );
+    }
+    let store = builder(None, "test", CertUsage::TlsServer)
+        .serial_allocator(&allocator)
+        .serial_number(100)
+        .build()
+        .unwrap();
+    assert_eq!(
+        store.certs()[0].serial_number().unwrap(),
+        SerialNumber::try_from(100u64).unwrap()
+    );
+    assert_eq!(allocator.0.get(), 3);
+}
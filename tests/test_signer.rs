@@ -0,0 +1,180 @@
+mod common;
+
+use common::{builder, gen_store};
+use openssl::{
+    hash::MessageDigest,
+    pkey::{PKey, Private},
+    x509::X509,
+};
+use pki::{
+    CertName, CertUsage, Certificate, CertificateBuilder, CertificateVerifier, KeyStore, PkiError,
+    PrivateKey, PublicKey, RecordingSigner, Result, SignatureAlgorithm, Signer,
+};
+
+/// External signer which holds the key outside of the key store and uses SHA-384
+struct ExternalSigner {
+    key: PKey<Private>,
+    certs: Vec<Certificate>,
+}
+
+impl Signer for ExternalSigner {
+    fn certs(&self) -> &[Certificate] {
+        &self.certs
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        PublicKey::from_der(&self.key.public_key_to_der()?)
+    }
+
+    fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        Ok(SignatureAlgorithm::EcdsaSha384)
+    }
+
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        let mut signer = openssl::sign::Signer::new(MessageDigest::sha384(), &self.key)?;
+        Ok(signer.sign_oneshot_to_vec(tbs)?)
+    }
+}
+
+/// Signer which returns a signature made by a wrong key
+struct BrokenSigner(KeyStore);
+
+impl Signer for BrokenSigner {
+    fn certs(&self) -> &[Certificate] {
+        self.0.certs()
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        self.0.private_key().public_key()
+    }
+
+    fn algorithm(&self) -> Result<SignatureAlgorithm> {
+        Ok(SignatureAlgorithm::EcdsaSha256)
+    }
+
+    fn sign(&self, tbs: &[u8]) -> Result<Vec<u8>> {
+        PrivateKey::new_ec(256)?.sign(tbs)
+    }
+}
+
+fn verify(root: &Certificate, store: &KeyStore) {
+    CertificateVerifier::new()
+        .default_paths(false)
+        .ca_root(root)
+        .verify(store.certs())
+        .unwrap();
+}
+
+fn signature_oid(cert: &Certificate) -> String {
+    X509::from_der(&cert.to_der().unwrap())
+        .unwrap()
+        .signature_algorithm()
+        .object()
+        .to_string()
+}
+
+#[test]
+fn test_external_signer() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let signer = ExternalSigner {
+        key: PKey::private_key_from_der(&root.private_key().to_der().unwrap()).unwrap(),
+        certs: root.certs().to_vec(),
+    };
+
+    let store = builder(None, "leaf", CertUsage::TlsServer)
+        .external_signer(&signer)
+        .build()
+        .unwrap();
+    assert_eq!(store.certs().len(), 2);
+    assert_eq!(signature_oid(&store.certs()[0]), "ecdsa-with-SHA384");
+    verify(&root.certs()[0], &store);
+
+    let boxed: Box<dyn Signer> = Box::new(signer);
+    let store = builder(None, "leaf", CertUsage::TlsServer)
+        .external_signer(&boxed)
+        .build()
+        .unwrap();
+    verify(&root.certs()[0], &store);
+}
+
+#[test]
+fn test_recording_signer() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let signer = RecordingSigner::new(root);
+
+    let store = builder(None, "leaf", CertUsage::TlsServer)
+        .external_signer(&signer)
+        .build()
+        .unwrap();
+    verify(&signer.inner().certs()[0], &store);
+
+    let requests = signer.requests();
+    assert_eq!(requests.len(), 1);
+    let der = store.certs()[0].to_der().unwrap();
+    assert!(der.windows(requests[0].len()).any(|w| w == requests[0]));
+    assert_eq!(signature_oid(&store.certs()[0]), "ecdsa-with-SHA256");
+}
+
+#[test]
+fn test_self_signed_external_key() {
+    let key = PrivateKey::new_rsa(2048).unwrap();
+    let signer = RecordingSigner::new(key.clone());
+
+    let cert = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "HSM Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .external_signer(&signer)
+        .build_certificate()
+        .unwrap();
+    assert_eq!(signer.requests().len(), 1);
+    assert!(cert.is_self_signed());
+    assert_eq!(cert.public_key().unwrap(), key.public_key().unwrap());
+    assert_eq!(signature_oid(&cert), "sha256WithRSAEncryption");
+
+    let root = KeyStore::new(key, [cert]).unwrap();
+    let store = builder(None, "leaf", CertUsage::TlsServer)
+        .signer(&root)
+        .build()
+        .unwrap();
+    verify(&root.certs()[0], &store);
+
+    let cert = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .signer(None)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build_certificate()
+        .unwrap();
+    assert!(cert.is_self_signed());
+}
+
+#[test]
+fn test_ed25519_signer() {
+    let key = PKey::generate_ed25519().unwrap();
+    let key = PrivateKey::from_der(&key.private_key_to_der().unwrap()).unwrap();
+    assert_eq!(key.algorithm().unwrap(), SignatureAlgorithm::Ed25519);
+    let root = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .private_key(key)
+        .build()
+        .unwrap();
+    assert_eq!(signature_oid(&root.certs()[0]), "ED25519");
+
+    let store = builder(None, "leaf", CertUsage::TlsServer)
+        .signer(&root)
+        .build()
+        .unwrap();
+    verify(&root.certs()[0], &store);
+}
+
+#[test]
+fn test_invalid_signature() {
+    let signer = BrokenSigner(gen_store(None, "Root CA", CertUsage::CA));
+    assert!(matches!(
+        builder(None, "leaf", CertUsage::TlsServer)
+            .external_signer(&signer)
+            .build(),
+        Err(PkiError::InvalidParameters)
+    ));
+}
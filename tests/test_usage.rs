@@ -0,0 +1,98 @@
+mod common;
+
+use common::gen_store;
+use pki::{
+    CertUsage, CertificateVerifier, KeyUsage, UsageProfile, VerifyPurpose, OID_EXTENDED_KEY_USAGE,
+    OID_IPSEC_IKE, OID_KEY_USAGE, OID_OCSP_SIGNING, OID_SERVER_AUTH, OID_TIME_STAMPING,
+};
+
+const CUSTOM_EKU: &str = "1.3.6.1.4.1.99999.7";
+
+#[test]
+fn test_usage_presets() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let root_cert = &root.certs()[0];
+    assert_eq!(
+        root_cert.key_usage().unwrap(),
+        Some(KeyUsage::KEY_CERT_SIGN | KeyUsage::CRL_SIGN)
+    );
+    assert_eq!(root_cert.extended_key_usage().unwrap(), None);
+
+    let store = gen_store(Some(&root), "leaf", CertUsage::TlsServer);
+    let cert = &store.certs()[0];
+    let key_usage = cert.key_usage().unwrap().unwrap();
+    assert_eq!(
+        key_usage.to_string(),
+        "digitalSignature,nonRepudiation,keyEncipherment,dataEncipherment"
+    );
+    assert_eq!(
+        key_usage.to_string(),
+        CertUsage::TlsServer.usage().to_string()
+    );
+    assert_eq!(
+        cert.extended_key_usage().unwrap(),
+        Some(vec![OID_SERVER_AUTH.to_owned()])
+    );
+    assert!(!cert.extension(OID_KEY_USAGE).unwrap().critical());
+}
+
+#[test]
+fn test_usage_profiles() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_root(&root.certs()[0]);
+
+    let store = gen_store(Some(&root), "leaf", UsageProfile::time_stamping());
+    let cert = &store.certs()[0];
+    assert!(cert.extension(OID_KEY_USAGE).unwrap().critical());
+    assert!(cert.extension(OID_EXTENDED_KEY_USAGE).unwrap().critical());
+    assert_eq!(
+        cert.extended_key_usage().unwrap(),
+        Some(vec![OID_TIME_STAMPING.to_owned()])
+    );
+    verifier
+        .purpose(VerifyPurpose::extended_usage([OID_TIME_STAMPING]))
+        .verify(store.certs())
+        .unwrap();
+    assert!(verifier
+        .purpose(CertUsage::TlsServer)
+        .verify(store.certs())
+        .is_err());
+
+    let store = gen_store(Some(&root), "leaf", UsageProfile::ocsp_signing());
+    assert_eq!(
+        store.certs()[0].key_usage().unwrap(),
+        Some(KeyUsage::DIGITAL_SIGNATURE)
+    );
+    verifier
+        .purpose(VerifyPurpose::extended_usage([OID_OCSP_SIGNING]))
+        .verify(store.certs())
+        .unwrap();
+
+    let profile = UsageProfile::new()
+        .key_usage(KeyUsage::KEY_AGREEMENT | KeyUsage::DECIPHER_ONLY)
+        .extended_usage(OID_IPSEC_IKE)
+        .extended_usage(CUSTOM_EKU)
+        .extended_usage(CUSTOM_EKU);
+    let store = gen_store(Some(&root), "leaf", profile);
+    let cert = &store.certs()[0];
+    let key_usage = cert.key_usage().unwrap().unwrap();
+    assert!(key_usage.contains(KeyUsage::DECIPHER_ONLY));
+    assert!(!key_usage.contains(KeyUsage::DIGITAL_SIGNATURE));
+    assert_eq!(
+        cert.extension(OID_KEY_USAGE).unwrap().value(),
+        [0x03, 0x03, 0x07, 0x08, 0x80]
+    );
+    assert_eq!(
+        cert.extended_key_usage().unwrap(),
+        Some(vec![OID_IPSEC_IKE.to_owned(), CUSTOM_EKU.to_owned()])
+    );
+    verifier
+        .purpose(VerifyPurpose::extended_usage([CUSTOM_EKU]))
+        .verify(store.certs())
+        .unwrap();
+
+    let store = gen_store(Some(&root), "leaf", UsageProfile::new());
+    assert_eq!(store.certs()[0].key_usage().unwrap(), None);
+    assert_eq!(store.certs()[0].extended_key_usage().unwrap(), None);
+}
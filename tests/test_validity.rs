@@ -0,0 +1,111 @@
+mod common;
+
+use common::builder;
+use pki::{CertTime, CertUsage, CertificateVerifier};
+
+fn contains(der: &[u8], needle: &[u8]) -> bool {
+    der.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+fn test_cert_time() {
+    let time = CertTime::from_ymd_hms(1969, 7, 20, 20, 17, 40).unwrap();
+    assert_eq!(time.unix_timestamp(), -14_182_940);
+    assert_eq!(time.to_ymd_hms(), (1969, 7, 20, 20, 17, 40));
+    assert_eq!(time.to_string(), "1969-07-20T20:17:40Z");
+    assert_eq!(CertTime::from(time.to_system_time()), time);
+
+    assert_eq!(
+        CertTime::from_ymd_hms(9999, 12, 31, 23, 59, 59).unwrap(),
+        CertTime::NO_EXPIRY
+    );
+    assert_eq!(CertTime::MIN.to_ymd_hms(), (0, 1, 1, 0, 0, 0));
+    assert!(CertTime::from_ymd_hms(2023, 2, 29, 0, 0, 0).is_err());
+    assert!(CertTime::from_ymd_hms(2024, 2, 29, 0, 0, 0).is_ok());
+    assert!(CertTime::NO_EXPIRY.add_secs(1).is_err());
+    assert_eq!(
+        CertTime::from_ymd_hms(2049, 12, 31, 0, 0, 0)
+            .unwrap()
+            .add_days(1)
+            .unwrap()
+            .to_ymd_hms(),
+        (2050, 1, 1, 0, 0, 0)
+    );
+}
+
+#[test]
+fn test_validity_encoding() {
+    let not_before = CertTime::from_ymd_hms(1949, 12, 31, 23, 59, 59).unwrap();
+    let not_after = CertTime::from_ymd_hms(2050, 1, 1, 0, 0, 0).unwrap();
+    let store = builder(None, "device", CertUsage::TlsServer)
+        .not_before(not_before)
+        .not_after(not_after)
+        .build()
+        .unwrap();
+    let cert = &store.certs()[0];
+    let der = cert.to_der().unwrap();
+    assert!(contains(&der, b"\x18\x0f19491231235959Z"));
+    assert!(contains(&der, b"\x18\x0f20500101000000Z"));
+    assert_eq!(cert.not_before().unwrap(), not_before);
+    assert_eq!(cert.not_after().unwrap(), not_after);
+
+    let not_before = CertTime::from_ymd_hms(1950, 1, 1, 0, 0, 0).unwrap();
+    let not_after = CertTime::from_ymd_hms(2049, 12, 31, 23, 59, 59).unwrap();
+    let der = builder(None, "device", CertUsage::TlsServer)
+        .not_before(not_before)
+        .not_after(not_after)
+        .build()
+        .unwrap()
+        .certs()[0]
+        .to_der()
+        .unwrap();
+    assert!(contains(&der, b"\x17\x0d500101000000Z"));
+    assert!(contains(&der, b"\x17\x0d491231235959Z"));
+}
+
+#[test]
+fn test_no_expiry() {
+    let not_before = CertTime::from_ymd_hms(1965, 3, 1, 0, 0, 0).unwrap();
+    let store = builder(None, "device", CertUsage::TlsServer)
+        .not_before(not_before)
+        .not_after(CertTime::NO_EXPIRY)
+        .build()
+        .unwrap();
+    let cert = &store.certs()[0];
+    assert!(contains(
+        &cert.to_der().unwrap(),
+        b"\x18\x0f99991231235959Z"
+    ));
+    assert!(cert.not_after().unwrap().is_no_expiry());
+    assert_eq!(cert.not_before().unwrap(), not_before);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_root(cert);
+    verifier.verify(store.certs()).unwrap();
+    verifier
+        .verification_time(CertTime::from_ymd_hms(9000, 1, 1, 0, 0, 0).unwrap())
+        .verify(store.certs())
+        .unwrap();
+    assert!(verifier
+        .verification_time(CertTime::from_ymd_hms(1964, 1, 1, 0, 0, 0).unwrap())
+        .verify(store.certs())
+        .is_err());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_interop() {
+    let time = CertTime::from_ymd_hms(1960, 5, 4, 3, 2, 1).unwrap();
+    let converted = time::OffsetDateTime::try_from(time).unwrap();
+    assert_eq!(converted.year(), 1960);
+    assert_eq!(CertTime::from(converted), time);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_interop() {
+    let time = CertTime::from_ymd_hms(2060, 5, 4, 3, 2, 1).unwrap();
+    let converted = chrono::DateTime::<chrono::Utc>::try_from(time).unwrap();
+    assert_eq!(converted.timestamp(), time.unix_timestamp());
+    assert_eq!(CertTime::from(converted), time);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::gen_store;
+use pki::{CertUsage, CertificateVerifier, KeyStore};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pki-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_trust_anchor_sources() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let other = gen_store(None, "Other CA", CertUsage::CA);
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA);
+    let leaf = gen_store(Some(&intermediate), "leaf", CertUsage::TlsServer);
+
+    let mut bundle = other.certs()[0].to_pem().unwrap();
+    bundle.extend(root.certs()[0].to_pem().unwrap());
+
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_bundle(&bundle).unwrap();
+    verifier.verify(leaf.certs()).unwrap();
+    assert!(CertificateVerifier::new().ca_bundle(b"garbage").is_err());
+
+    let dir = temp_dir("anchors");
+    let bundle_file = dir.join("bundle.pem");
+    fs::write(&bundle_file, &bundle).unwrap();
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_bundle_file(&bundle_file)
+        .unwrap();
+    verifier.verify(leaf.certs()).unwrap();
+
+    let hashed = dir.join("hashed");
+    fs::create_dir_all(&hashed).unwrap();
+    let root_cert = &root.certs()[0];
+    fs::write(
+        hashed.join(format!("{:08x}.0", root_cert.subject_name().openssl_hash())),
+        root_cert.to_pem().unwrap(),
+    )
+    .unwrap();
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_dir(&hashed).unwrap();
+    verifier.verify(leaf.certs()).unwrap();
+
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_key_store(&intermediate);
+    verifier.verify(leaf.certs()).unwrap();
+
+    // the leaf certificate of the key store is not trusted
+    let leaf_only = KeyStore::new(leaf.private_key().clone(), leaf.certs()[..1].to_vec()).unwrap();
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .partial_chain(true)
+        .ca_key_store(&leaf_only);
+    assert!(verifier.verify(leaf_only.certs()).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_partial_chain() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA);
+    let leaf = gen_store(Some(&intermediate), "leaf", CertUsage::TlsServer);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(&intermediate.certs()[0]);
+    assert!(verifier.verify(&leaf.certs()[..1]).is_err());
+
+    verifier.partial_chain(true);
+    let report = verifier.verify_detailed(&leaf.certs()[..1]).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.path().len(), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_ca_dir() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let mut verifier = CertificateVerifier::new();
+    assert!(matches!(
+        verifier.ca_dir(OsStr::from_bytes(b"/tmp/pki-\xff")),
+        Err(pki::PkiError::InvalidParameters)
+    ));
+}
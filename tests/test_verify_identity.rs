@@ -0,0 +1,82 @@
+mod common;
+
+use std::net::IpAddr;
+
+use common::{builder, gen_store};
+use pki::{CertUsage, CertificateVerifier, PkiError};
+
+fn assert_mismatch(result: pki::Result<()>) {
+    assert!(matches!(result, Err(PkiError::IdentityMismatch(_))));
+}
+
+#[test]
+fn test_verify_identity() {
+    let ca = gen_store(None, "Root CA", CertUsage::CA);
+    let store = builder(Some(&ca), "legacy.example.org", CertUsage::TlsServer)
+        .alt_names(["*.example.com", "10.0.0.1", "::1", "admin@example.com"])
+        .build()
+        .unwrap();
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(ca.certs().last().unwrap());
+    let chain = store.certs();
+
+    verifier.verify_for_host(chain, "api.example.com").unwrap();
+    assert_mismatch(verifier.verify_for_host(chain, "a.b.example.com"));
+    assert_mismatch(verifier.verify_for_host(chain, "example.com"));
+    assert_mismatch(verifier.verify_for_host(chain, "legacy.example.org"));
+
+    verifier
+        .verify_for_ip(chain, "10.0.0.1".parse::<IpAddr>().unwrap())
+        .unwrap();
+    verifier
+        .verify_for_ip(chain, "::1".parse::<IpAddr>().unwrap())
+        .unwrap();
+    assert_mismatch(verifier.verify_for_ip(chain, "10.0.0.2".parse::<IpAddr>().unwrap()));
+
+    verifier
+        .verify_for_email(chain, "admin@example.com")
+        .unwrap();
+    assert_mismatch(verifier.verify_for_email(chain, "root@example.com"));
+
+    let other_ca = gen_store(None, "Root CA", CertUsage::CA);
+    let mut other_verifier = CertificateVerifier::new();
+    other_verifier
+        .default_paths(false)
+        .ca_root(other_ca.certs().last().unwrap());
+    assert!(matches!(
+        other_verifier.verify_for_host(chain, "api.example.com"),
+        Err(PkiError::Verify(_))
+    ));
+}
+
+#[test]
+fn test_verify_identity_options() {
+    let ca = gen_store(None, "Root CA", CertUsage::CA);
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(ca.certs().last().unwrap());
+
+    let store = builder(Some(&ca), "legacy.example.org", CertUsage::TlsServer)
+        .alt_names(["10.0.0.1"])
+        .build()
+        .unwrap();
+    assert_mismatch(verifier.verify_for_host(store.certs(), "legacy.example.org"));
+    verifier.cn_fallback(true);
+    verifier
+        .verify_for_host(store.certs(), "legacy.example.org")
+        .unwrap();
+
+    let store = builder(Some(&ca), "partial", CertUsage::TlsServer)
+        .alt_names(["api*.example.com"])
+        .build()
+        .unwrap();
+    assert_mismatch(verifier.verify_for_host(store.certs(), "api1.example.com"));
+    verifier.partial_wildcards(true);
+    verifier
+        .verify_for_host(store.certs(), "api1.example.com")
+        .unwrap();
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{builder, gen_store};
+use pki::{CertUsage, CertificateVerifier, PrivateKey};
+
+fn subjects(path: &[pki::Certificate]) -> Vec<String> {
+    path.iter()
+        .map(|cert| cert.subject_name().to_string())
+        .collect()
+}
+
+#[test]
+fn test_verify_unordered_intermediates() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA);
+    let issuing = gen_store(Some(&intermediate), "Issuing CA", CertUsage::CA);
+    let leaf = gen_store(Some(&issuing), "leaf", CertUsage::TlsServer);
+    let unrelated = gen_store(None, "Other CA", CertUsage::CA);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(root.certs().last().unwrap());
+
+    let bag = [
+        unrelated.certs()[0].clone(),
+        intermediate.certs()[0].clone(),
+        issuing.certs()[0].clone(),
+        intermediate.certs()[0].clone(),
+    ];
+    let path = verifier
+        .verify_with_intermediates(&leaf.certs()[0], &bag)
+        .unwrap();
+    assert_eq!(
+        subjects(&path),
+        [
+            "CN=leaf",
+            "CN=Issuing CA",
+            "CN=Intermediate CA",
+            "CN=Root CA"
+        ]
+    );
+
+    assert!(verifier
+        .verify_with_intermediates(&leaf.certs()[0], &bag[..2])
+        .is_err());
+}
+
+#[test]
+fn test_verify_cross_signed() {
+    let old_root = gen_store(None, "Old Root CA", CertUsage::CA);
+    let new_root = gen_store(None, "New Root CA", CertUsage::CA);
+
+    let key = PrivateKey::new_ec(256).unwrap();
+    let intermediate = builder(Some(&new_root), "Intermediate CA", CertUsage::CA)
+        .private_key(key.clone())
+        .build()
+        .unwrap();
+    let cross_signed = builder(Some(&old_root), "Intermediate CA", CertUsage::CA)
+        .private_key(key)
+        .build()
+        .unwrap();
+    let leaf = gen_store(Some(&intermediate), "leaf", CertUsage::TlsServer);
+
+    let bag = [
+        intermediate.certs()[0].clone(),
+        cross_signed.certs()[0].clone(),
+        new_root.certs()[0].clone(),
+    ];
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(old_root.certs().last().unwrap());
+    let path = verifier
+        .verify_with_intermediates(&leaf.certs()[0], &bag)
+        .unwrap();
+    assert_eq!(
+        subjects(&path),
+        ["CN=leaf", "CN=Intermediate CA", "CN=Old Root CA"]
+    );
+    assert_eq!(
+        path[1].to_der().unwrap(),
+        cross_signed.certs()[0].to_der().unwrap()
+    );
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(new_root.certs().last().unwrap());
+    let path = verifier
+        .verify_with_intermediates(&leaf.certs()[0], &bag)
+        .unwrap();
+    assert_eq!(
+        subjects(&path),
+        ["CN=leaf", "CN=Intermediate CA", "CN=New Root CA"]
+    );
+}
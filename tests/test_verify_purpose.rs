@@ -0,0 +1,161 @@
+mod common;
+
+use common::{builder, gen_store};
+use openssl::{
+    asn1::Asn1Time,
+    hash::MessageDigest,
+    pkey::PKey,
+    x509::{
+        extension::{BasicConstraints, ExtendedKeyUsage, KeyUsage},
+        X509,
+    },
+};
+use pki::{
+    CertUsage, Certificate, CertificateVerifier, KeyStore, PkiError, PrivateKey, UsageProfile,
+    VerifyPurpose, OID_SERVER_AUTH,
+};
+
+// intermediate CA restricted to the client authentication
+fn gen_restricted_intermediate(root: &KeyStore) -> KeyStore {
+    let key = PrivateKey::new_ec(256).unwrap();
+    let root_cert = X509::from_der(&root.certs()[0].to_der().unwrap()).unwrap();
+    let root_key = PKey::private_key_from_der(&root.private_key().to_der().unwrap()).unwrap();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder
+        .set_pubkey(&PKey::private_key_from_der(&key.to_der().unwrap()).unwrap())
+        .unwrap();
+    builder
+        .set_subject_name(&openssl_name("Client CA"))
+        .unwrap();
+    builder.set_issuer_name(root_cert.subject_name()).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    builder
+        .append_extension(BasicConstraints::new().ca().critical().build().unwrap())
+        .unwrap();
+    builder
+        .append_extension(KeyUsage::new().key_cert_sign().build().unwrap())
+        .unwrap();
+    builder
+        .append_extension(ExtendedKeyUsage::new().client_auth().build().unwrap())
+        .unwrap();
+    builder.sign(&root_key, MessageDigest::sha256()).unwrap();
+
+    let cert = Certificate::from_der(&builder.build().to_der().unwrap()).unwrap();
+    KeyStore::new(key, [cert, root.certs()[0].clone()]).unwrap()
+}
+
+fn openssl_name(cn: &str) -> openssl::x509::X509Name {
+    let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", cn).unwrap();
+    name.build()
+}
+
+#[test]
+fn test_verify_purpose() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let server = gen_store(Some(&root), "server", CertUsage::TlsServer);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(root.certs().last().unwrap());
+    verifier.verify(server.certs()).unwrap();
+
+    verifier.purpose(CertUsage::TlsServer);
+    verifier.verify(server.certs()).unwrap();
+
+    verifier.purpose(CertUsage::TlsClient);
+    match verifier.verify(server.certs()) {
+        Err(PkiError::PurposeMismatch { depth, subject, .. }) => {
+            assert_eq!(depth, 0);
+            assert_eq!(subject, "CN=server");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    verifier.purpose(VerifyPurpose::extended_usage(["1.3.6.1.5.5.7.3.1"]));
+    verifier.verify(server.certs()).unwrap();
+    verifier.purpose(VerifyPurpose::extended_usage(["1.3.6.1.5.5.7.3.8"]));
+    assert!(matches!(
+        verifier.verify(server.certs()),
+        Err(PkiError::PurposeMismatch { depth: 0, .. })
+    ));
+
+    verifier.purpose(CertUsage::CA);
+    assert!(matches!(
+        verifier.verify(server.certs()),
+        Err(PkiError::PurposeMismatch { depth: 0, .. })
+    ));
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA);
+    verifier.verify(intermediate.certs()).unwrap();
+}
+
+#[test]
+fn test_verify_purpose_chain() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let intermediate = gen_restricted_intermediate(&root);
+    let client = gen_store(Some(&intermediate), "client", CertUsage::TlsServerAndClient);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(root.certs().last().unwrap());
+
+    verifier.purpose(CertUsage::TlsClient);
+    verifier.verify(client.certs()).unwrap();
+
+    verifier.purpose(CertUsage::TlsServer);
+    match verifier.verify(client.certs()) {
+        Err(PkiError::PurposeMismatch { depth, subject, .. }) => {
+            assert_eq!(depth, 1);
+            assert_eq!(subject, "CN=Client CA");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_verify_purpose_key_usage() {
+    let root = gen_store(None, "Root CA", CertUsage::CA);
+    let crl_signer = builder(Some(&root), "CRL signer", CertUsage::TlsServer)
+        .usage(
+            UsageProfile::new()
+                .key_usage(pki::KeyUsage::CRL_SIGN)
+                .extended_usage(OID_SERVER_AUTH),
+        )
+        .build()
+        .unwrap();
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(root.certs().last().unwrap());
+
+    // the extended key usage allows TLS server authentication, the key usage does not
+    verifier.purpose(CertUsage::TlsServer);
+    match verifier.verify(crl_signer.certs()) {
+        Err(PkiError::PurposeMismatch { depth, subject, .. }) => {
+            assert_eq!(depth, 0);
+            assert_eq!(subject, "CN=CRL signer");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    verifier.purpose(VerifyPurpose::extended_usage([OID_SERVER_AUTH]));
+    verifier.verify(crl_signer.certs()).unwrap();
+
+    let server = gen_store(Some(&root), "server", CertUsage::TlsServer);
+    let both = gen_store(Some(&root), "both", CertUsage::TlsServerAndClient);
+    verifier.purpose(CertUsage::TlsServerAndClient);
+    verifier.verify(both.certs()).unwrap();
+    assert!(matches!(
+        verifier.verify(server.certs()),
+        Err(PkiError::PurposeMismatch { depth: 0, .. })
+    ));
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use common::builder;
+use pki::{CertUsage, CertificateVerifier, KeyStore, PkiError};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn gen_store(signer: Option<&KeyStore>, cn: &str, usage: CertUsage, expired: bool) -> KeyStore {
+    let now = SystemTime::now();
+    builder(signer, cn, usage)
+        .not_before(now - 10 * DAY)
+        .not_after(if expired { now - DAY } else { now + DAY })
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_verify_detailed() {
+    let root = gen_store(None, "Root CA", CertUsage::CA, false);
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA, false);
+    let leaf = gen_store(Some(&intermediate), "leaf", CertUsage::TlsServer, false);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(root.certs().last().unwrap());
+
+    let report = verifier.verify_detailed(leaf.certs()).unwrap();
+    assert!(report.is_valid());
+    let subjects: Vec<_> = report
+        .path()
+        .iter()
+        .map(|cert| cert.subject_name().to_string())
+        .collect();
+    assert_eq!(subjects, ["CN=leaf", "CN=Intermediate CA", "CN=Root CA"]);
+    assert_eq!(report.into_result().unwrap().len(), 3);
+}
+
+#[test]
+fn test_verify_detailed_failures() {
+    let root = gen_store(None, "Root CA", CertUsage::CA, false);
+    let intermediate = gen_store(Some(&root), "Intermediate CA", CertUsage::CA, true);
+    let leaf = gen_store(Some(&intermediate), "leaf", CertUsage::TlsServer, true);
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(root.certs().last().unwrap())
+        .purpose(CertUsage::TlsClient);
+
+    let report = verifier.verify_detailed(leaf.certs()).unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(report.path().len(), 3);
+
+    let failures: Vec<_> = report
+        .failures()
+        .iter()
+        .map(|f| (f.depth(), f.subject().unwrap().to_string()))
+        .collect();
+    assert!(failures.contains(&(0, "CN=leaf".to_owned())));
+    assert!(failures.contains(&(1, "CN=Intermediate CA".to_owned())));
+    assert!(report
+        .failures()
+        .iter()
+        .any(|f| matches!(f.error(), PkiError::PurposeMismatch { depth: 0, .. })));
+    assert_eq!(
+        report
+            .failures()
+            .iter()
+            .filter(|f| matches!(f.error(), PkiError::Verify(_)))
+            .count(),
+        2
+    );
+
+    assert!(matches!(
+        verifier.verify(leaf.certs()),
+        Err(PkiError::Verify(_))
+    ));
+    assert!(report.into_result().is_err());
+}
@@ -0,0 +1,96 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use pki::{CertName, CertUsage, CertificateBuilder, CertificateVerifier, PkiError, PrivateKey};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+#[test]
+fn test_verification_time() {
+    let now = SystemTime::now();
+    let ca = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .not_before(now - HOUR)
+        .not_after(now + 24 * HOUR)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "host")]).unwrap())
+        .signer(&ca)
+        .not_before(now + HOUR)
+        .not_after(now + 2 * HOUR)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+
+    let mut verifier = CertificateVerifier::new();
+    verifier
+        .default_paths(false)
+        .ca_root(ca.certs().last().unwrap());
+    assert!(matches!(
+        verifier.verify(store.certs()),
+        Err(PkiError::Verify(_))
+    ));
+
+    verifier.verification_time(now + 90 * Duration::from_secs(60));
+    verifier.verify(store.certs()).unwrap();
+
+    verifier.verification_time(now + 3 * HOUR);
+    assert!(verifier.verify(store.certs()).is_err());
+
+    verifier.clock_skew(2 * HOUR);
+    verifier.verify(store.certs()).unwrap();
+
+    verifier.verification_time(now - HOUR / 2);
+    verifier.verify(store.certs()).unwrap();
+
+    verifier.verification_time(now - 4 * HOUR);
+    assert!(verifier.verify(store.certs()).is_err());
+    // the tolerance saturates at the encodable date range instead of overflowing
+    verifier.clock_skew(Duration::MAX);
+    verifier.verify(store.certs()).unwrap();
+    verifier.clock_skew(Duration::from_secs(i64::MAX as u64 + 1));
+    verifier.verify(store.certs()).unwrap();
+}
+
+#[test]
+fn test_verify_at_second_boundary() {
+    // the certificate is valid from the first second of not_before, no earlier
+    let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let end = start + HOUR;
+    let ca = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "Root CA")]).unwrap())
+        .usage(CertUsage::CA)
+        .not_before(start)
+        .not_after(end)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let store = CertificateBuilder::new()
+        .subject(CertName::new([("CN", "host")]).unwrap())
+        .signer(&ca)
+        .not_before(start)
+        .not_after(end)
+        .private_key(PrivateKey::new_ec(256).unwrap())
+        .build()
+        .unwrap();
+    let mut verifier = CertificateVerifier::new();
+    verifier.default_paths(false).ca_root(&ca.certs()[0]);
+
+    for time in [
+        start,
+        start + Duration::from_secs(1),
+        end - Duration::from_secs(1),
+    ] {
+        verifier.verification_time(time);
+        verifier.verify(store.certs()).unwrap();
+    }
+    for time in [start - Duration::from_secs(1), end + Duration::from_secs(1)] {
+        verifier.verification_time(time);
+        assert!(matches!(
+            verifier.verify(store.certs()),
+            Err(PkiError::Verify(_))
+        ));
+    }
+}